@@ -0,0 +1,257 @@
+use std::fs::File;
+use std::io::{IsTerminal, Read, Write};
+use std::process::Command;
+use std::sync::{mpsc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// Set to `light` or `dark` to skip detection.
+pub const ENV_VAR: &str = "HYPR_SHOWKEY_APPEARANCE";
+
+/// How long to wait for the terminal to answer the background color query.
+const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+static DETECTED: OnceLock<Detected> = OnceLock::new();
+
+/// Whether the light or dark variant of `theme: { light, dark }` is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Appearance {
+    Light,
+    Dark,
+}
+
+/// An appearance and where it came from, for `hypr-showkey check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Detected {
+    pub appearance: Appearance,
+    pub source: &'static str,
+}
+
+impl Appearance {
+    pub fn label(self) -> &'static str {
+        match self {
+            Appearance::Light => "light",
+            Appearance::Dark => "dark",
+        }
+    }
+}
+
+/// The appearance, detected once per run. Only the TUI calls this, before it
+/// takes over the terminal, so reloading the config later doesn't query the
+/// terminal while the TUI is reading from it.
+pub fn detect() -> Detected {
+    *DETECTED.get_or_init(|| {
+        detect_with(
+            std::env::var(ENV_VAR).ok(),
+            query_terminal,
+            desktop_color_scheme,
+        )
+    })
+}
+
+/// The appearance without asking the terminal or the desktop: what `detect`
+/// found earlier in this run, else the env override, else dark.
+pub fn known() -> Detected {
+    if let Some(detected) = DETECTED.get() {
+        return *detected;
+    }
+    match env_override(std::env::var(ENV_VAR).ok()) {
+        Some(appearance) => detected(appearance, ENV_VAR),
+        None => detected(Appearance::Dark, "default, only the TUI detects it"),
+    }
+}
+
+fn env_override(env: Option<String>) -> Option<Appearance> {
+    let value = env.filter(|value| !value.is_empty())?;
+    match value.to_lowercase().as_str() {
+        "light" => Some(Appearance::Light),
+        "dark" => Some(Appearance::Dark),
+        _ => {
+            eprintln!(
+                "Warning: {} should be `light` or `dark`, not `{}`",
+                ENV_VAR, value
+            );
+            None
+        }
+    }
+}
+
+/// The env override, then the terminal's background, then the desktop's
+/// `color-scheme`; dark when none of them tells.
+fn detect_with(
+    env: Option<String>,
+    terminal: impl FnOnce() -> Option<Appearance>,
+    desktop: impl FnOnce() -> Option<Appearance>,
+) -> Detected {
+    if let Some(appearance) = env_override(env) {
+        return detected(appearance, ENV_VAR);
+    }
+    if let Some(appearance) = terminal() {
+        return detected(appearance, "terminal background");
+    }
+    if let Some(appearance) = desktop() {
+        return detected(appearance, "desktop color-scheme");
+    }
+    detected(Appearance::Dark, "default, nothing detected")
+}
+
+fn detected(appearance: Appearance, source: &'static str) -> Detected {
+    Detected { appearance, source }
+}
+
+/// Ask the terminal for its background color with OSC 11. A primary device
+/// attributes query (DA1) follows it: every terminal answers that one, so
+/// the reply is complete even when OSC 11 isn't supported.
+fn query_terminal() -> Option<Appearance> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let reply = tty
+        .write_all(b"\x1b]11;?\x1b\\\x1b[c")
+        .and_then(|_| tty.flush())
+        .ok()
+        .and_then(|_| read_reply(tty));
+    let _ = crossterm::terminal::disable_raw_mode();
+
+    parse_background_reply(&String::from_utf8_lossy(&reply?))
+}
+
+/// Read from the terminal until the DA1 answer ends the reply, giving up
+/// after `QUERY_TIMEOUT`. A thread does the reading so the wait can time
+/// out; it stops once the DA1 answer is in, which every terminal sends, so
+/// it doesn't linger and take the TUI's key presses.
+fn read_reply(mut tty: File) -> Option<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut reply = Vec::new();
+        let mut buffer = [0u8; 64];
+        while !da1_complete(&reply) {
+            match tty.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => reply.extend_from_slice(&buffer[..read]),
+            }
+        }
+        let _ = sender.send(reply);
+    });
+
+    receiver.recv_timeout(QUERY_TIMEOUT).ok()
+}
+
+/// Whether `reply` holds a whole DA1 answer, `ESC [ ? ... c`.
+fn da1_complete(reply: &[u8]) -> bool {
+    reply
+        .windows(3)
+        .position(|w| w == b"\x1b[?")
+        .is_some_and(|start| reply[start..].contains(&b'c'))
+}
+
+/// The appearance from an OSC 11 reply such as
+/// `ESC ] 11 ; rgb:1e1e/1e1e/2e2e ESC \`, by the background's luminance.
+fn parse_background_reply(reply: &str) -> Option<Appearance> {
+    let start = reply.find("]11;rgb:")? + "]11;rgb:".len();
+    let rgb = reply[start..].split(['\x07', '\x1b']).next()?;
+    let channels: Vec<f64> = rgb
+        .split('/')
+        .map(|channel| {
+            let value = u32::from_str_radix(channel, 16).ok()?;
+            let max = 16u32.checked_pow(channel.len() as u32)? - 1;
+            Some(value as f64 / max as f64)
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b] = channels[..] else {
+        return None;
+    };
+
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance < 0.5 {
+        Appearance::Dark
+    } else {
+        Appearance::Light
+    })
+}
+
+/// The freedesktop `color-scheme` preference, as GNOME and the settings
+/// portal store it, read with `gsettings` or else `dconf`.
+fn desktop_color_scheme() -> Option<Appearance> {
+    let lookups: [(&str, &[&str]); 2] = [
+        (
+            "gsettings",
+            &["get", "org.gnome.desktop.interface", "color-scheme"],
+        ),
+        (
+            "dconf",
+            &["read", "/org/gnome/desktop/interface/color-scheme"],
+        ),
+    ];
+    lookups.iter().find_map(|(program, args)| {
+        let output = Command::new(program).args(*args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_color_scheme(&String::from_utf8_lossy(&output.stdout))
+    })
+}
+
+/// `'prefer-dark'` or `'prefer-light'`; `'default'` states no preference.
+fn parse_color_scheme(value: &str) -> Option<Appearance> {
+    match value.trim().trim_matches('\'') {
+        "prefer-dark" => Some(Appearance::Dark),
+        "prefer-light" => Some(Appearance::Light),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_appearance() {
+        assert_eq!(
+            parse_background_reply("\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\\x1b[?62;22c"),
+            Some(Appearance::Dark)
+        );
+        assert_eq!(
+            parse_background_reply("\x1b]11;rgb:ef/f1/f5\x07"),
+            Some(Appearance::Light)
+        );
+        assert_eq!(parse_background_reply("\x1b[?62;22c"), None);
+        assert!(!da1_complete(b"\x1b]11;rgb:1c1c/1c1c/1c1c\x1b\\"));
+        assert!(!da1_complete(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;2"));
+        assert!(da1_complete(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;22c"));
+        assert_eq!(
+            parse_color_scheme("'prefer-light'\n"),
+            Some(Appearance::Light)
+        );
+        assert_eq!(parse_color_scheme("'default'\n"), None);
+
+        let light = || Some(Appearance::Light);
+        let unknown = || None;
+        let detect = |env: Option<&str>, terminal: fn() -> Option<Appearance>| {
+            detect_with(env.map(String::from), terminal, || Some(Appearance::Dark))
+        };
+        assert_eq!(
+            detect(Some("DARK"), light),
+            detected(Appearance::Dark, ENV_VAR)
+        );
+        assert_eq!(
+            detect(None, light),
+            detected(Appearance::Light, "terminal background")
+        );
+        assert_eq!(
+            detect(None, unknown),
+            detected(Appearance::Dark, "desktop color-scheme")
+        );
+        assert_eq!(
+            detect_with(None, unknown, unknown).source,
+            "default, nothing detected"
+        );
+    }
+}
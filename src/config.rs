@@ -1,20 +1,95 @@
+use crate::appearance::{Appearance, Detected};
+use crate::keymap::Keymap;
+use crate::paths;
+use crate::themes::{self, SchemeFormat};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
-    pub hyprland_configs: HyprlandConfigs,
     #[serde(default)]
+    pub hyprland_configs: HyprlandConfigs,
+    #[serde(default = "default_categories")]
     pub categories: HashMap<String, Category>,
     #[serde(default)]
     pub ui: UiSettings,
+    /// The file this was loaded from, if any.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// Problems that didn't stop loading, such as a theme that wasn't
+    /// found. The caller reports them.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HyprlandConfigs {
-    pub files: Vec<String>,
+    /// Files to read; Hyprland's own config when empty.
+    #[serde(default)]
+    pub files: Vec<HyprlandFile>,
+    /// Directory that relative `files` are relative to. Itself relative to
+    /// the showkey.yaml; `~/.config/hypr` by default.
+    #[serde(default)]
+    pub base_dir: Option<String>,
+    /// Use the comment block above a bind for its description and
+    /// `@category`, `@desc` and `@hidden` annotations.
+    #[serde(default = "default_doc_comments")]
+    pub doc_comments: bool,
+    /// Put binds under section comments like `# === Workspaces ===` in a
+    /// category named after the section.
+    #[serde(default)]
+    pub section_categories: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            hyprland_configs: HyprlandConfigs::default(),
+            categories: default_categories(),
+            ui: UiSettings::default(),
+            path: None,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl Default for HyprlandConfigs {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            base_dir: None,
+            doc_comments: default_doc_comments(),
+            section_categories: false,
+        }
+    }
+}
+
+/// An entry of `hyprland_configs.files`: a path, or a path with options.
+/// Paths may start with `~`, use `$VAR` or `${VAR}` and contain globs.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum HyprlandFile {
+    Path(String),
+    Detailed {
+        path: String,
+        /// Don't warn when the file doesn't exist.
+        #[serde(default)]
+        optional: bool,
+    },
+}
+
+impl HyprlandFile {
+    pub fn path(&self) -> &str {
+        match self {
+            HyprlandFile::Path(path) | HyprlandFile::Detailed { path, .. } => path,
+        }
+    }
+
+    pub fn optional(&self) -> bool {
+        matches!(self, HyprlandFile::Detailed { optional: true, .. })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -36,6 +111,235 @@ pub struct UiSettings {
     pub max_results: usize,
     #[serde(default)]
     pub theme: ThemeSettings,
+    #[serde(default)]
+    pub input_style: InputStyle,
+    #[serde(default)]
+    pub enter_action: EnterAction,
+    #[serde(default)]
+    pub sort: SortMode,
+    /// Capture the mouse for clicking and scrolling. Turn this off to keep
+    /// the terminal's native text selection.
+    #[serde(default = "default_mouse")]
+    pub mouse: bool,
+    /// `ansi`, `iso` or a path to a layout file for the keyboard view.
+    #[serde(default = "default_keyboard_layout")]
+    pub keyboard_layout: String,
+    /// Also list binds inside false `# hyprlang if` blocks, greyed out.
+    #[serde(default)]
+    pub show_inactive: bool,
+    /// Keys for the TUI's actions, over the defaults.
+    #[serde(default)]
+    pub keymap: Keymap,
+    #[serde(default)]
+    pub layout: ListLayout,
+    /// Columns of the table layout.
+    #[serde(default)]
+    pub table: TableSettings,
+}
+
+/// How the keybindings are shown in the list view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListLayout {
+    /// `key → description`, in as many columns as the terminal fits.
+    #[default]
+    List,
+    /// One row per binding with aligned columns and a header.
+    Table,
+}
+
+impl ListLayout {
+    pub fn next(self) -> Self {
+        match self {
+            ListLayout::List => ListLayout::Table,
+            ListLayout::Table => ListLayout::List,
+        }
+    }
+}
+
+/// A column of the table layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableColumn {
+    /// The key combination, or only the key when `mods` is shown too.
+    Key,
+    /// The modifiers, e.g. `SUPER + SHIFT`.
+    Mods,
+    /// The description, or the action when there is none.
+    Description,
+    /// The dispatcher, e.g. `exec`.
+    Dispatcher,
+    /// The dispatcher's arguments.
+    Params,
+    Category,
+    /// The letters after `bind`, e.g. `el` for `bindel`.
+    Flags,
+    /// The file and line the bind is defined at.
+    File,
+}
+
+impl TableColumn {
+    pub fn label(self) -> &'static str {
+        match self {
+            TableColumn::Key => "Key",
+            TableColumn::Mods => "Mods",
+            TableColumn::Description => "Description",
+            TableColumn::Dispatcher => "Dispatcher",
+            TableColumn::Params => "Params",
+            TableColumn::Category => "Category",
+            TableColumn::Flags => "Flags",
+            TableColumn::File => "File",
+        }
+    }
+
+    /// The sort mode that orders the list by this column.
+    pub fn sort_mode(self) -> Option<SortMode> {
+        match self {
+            TableColumn::Key => Some(SortMode::Key),
+            TableColumn::Mods => Some(SortMode::Modifiers),
+            TableColumn::Description => Some(SortMode::Description),
+            TableColumn::Dispatcher => Some(SortMode::Dispatcher),
+            TableColumn::Category => Some(SortMode::Category),
+            TableColumn::File => Some(SortMode::File),
+            TableColumn::Params | TableColumn::Flags => None,
+        }
+    }
+}
+
+/// `ui.table`: which columns the table layout shows, and fixed widths for
+/// some of them.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "TableSettingsFile")]
+pub struct TableSettings {
+    pub columns: Vec<TableColumn>,
+    /// Widths in cells; columns without one fit their content, and the
+    /// description and params columns share what is left.
+    pub widths: HashMap<TableColumn, u16>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TableSettingsFile {
+    #[serde(default = "default_table_columns")]
+    columns: Vec<TableColumn>,
+    #[serde(default)]
+    widths: HashMap<TableColumn, u16>,
+}
+
+impl TryFrom<TableSettingsFile> for TableSettings {
+    type Error = String;
+
+    fn try_from(file: TableSettingsFile) -> Result<Self, Self::Error> {
+        if file.columns.is_empty() {
+            return Err("table.columns: list at least one column".to_string());
+        }
+        for (i, column) in file.columns.iter().enumerate() {
+            if file.columns[..i].contains(column) {
+                return Err(format!(
+                    "table.columns: `{}` is listed twice",
+                    column.label().to_lowercase()
+                ));
+            }
+        }
+        Ok(Self {
+            columns: file.columns,
+            widths: file.widths,
+        })
+    }
+}
+
+impl Default for TableSettings {
+    fn default() -> Self {
+        Self {
+            columns: default_table_columns(),
+            widths: HashMap::new(),
+        }
+    }
+}
+
+/// What pressing Enter on a keybinding does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnterAction {
+    /// Quit and print the selected keybinding to stdout.
+    #[default]
+    Select,
+    /// Copy the key combination to the clipboard with `wl-copy`.
+    Copy,
+    /// Quit and run the binding's dispatcher through `hyprctl dispatch`.
+    Execute,
+}
+
+/// Order of the keybinding list, and of matches that score the same while
+/// searching. Bindings that tie keep their file order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortMode {
+    /// The order bindings appear in the Hyprland config files.
+    #[default]
+    File,
+    /// By key name, then modifiers.
+    Key,
+    /// Grouped by modifier set (fewest modifiers first), then by key.
+    Modifiers,
+    /// By category name.
+    Category,
+    /// By dispatcher, e.g. `exec` or `workspace`.
+    Dispatcher,
+    /// By description.
+    Description,
+    /// Most recently used bindings first.
+    Recent,
+    /// Most often used bindings first.
+    Frequent,
+}
+
+impl SortMode {
+    const ALL: [SortMode; 8] = [
+        SortMode::File,
+        SortMode::Key,
+        SortMode::Modifiers,
+        SortMode::Category,
+        SortMode::Dispatcher,
+        SortMode::Description,
+        SortMode::Recent,
+        SortMode::Frequent,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::File => "file",
+            SortMode::Key => "key",
+            SortMode::Modifiers => "modifiers",
+            SortMode::Category => "category",
+            SortMode::Dispatcher => "dispatcher",
+            SortMode::Description => "description",
+            SortMode::Recent => "recent",
+            SortMode::Frequent => "frequent",
+        }
+    }
+}
+
+/// How keys typed in the TUI are split between searching and navigation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputStyle {
+    /// Vim-like: an insert mode for typing the query and a normal mode for
+    /// navigating with j/k and quitting with q.
+    #[default]
+    Modal,
+    /// Always searching; navigate with the arrows or Ctrl-j/k/n/p.
+    Ctrl,
 }
 
 impl Default for UiSettings {
@@ -46,6 +350,15 @@ impl Default for UiSettings {
             show_raw_command: false,
             max_results: default_max_results(),
             theme: ThemeSettings::default(),
+            input_style: InputStyle::default(),
+            enter_action: EnterAction::default(),
+            sort: SortMode::default(),
+            mouse: default_mouse(),
+            keyboard_layout: default_keyboard_layout(),
+            show_inactive: false,
+            keymap: Keymap::default(),
+            layout: ListLayout::default(),
+            table: TableSettings::default(),
         }
     }
 }
@@ -54,6 +367,33 @@ impl Default for UiSettings {
 pub struct ThemeSettings {
     pub name: String,
     pub colors: ThemeColors,
+    pub styles: ThemeStyles,
+    pub symbols: ThemeSymbols,
+    /// The colors set in the config on top of the preset.
+    #[serde(skip)]
+    pub overrides: Vec<String>,
+    /// How the theme was picked with `theme: { light, dark }`.
+    #[serde(skip)]
+    pub appearance: Option<Detected>,
+    /// Theme files the colors were read from, watched for hot reload.
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
+    /// `ui.theme` as written, for `resolve`.
+    #[serde(skip)]
+    spec: ThemeSpec,
+}
+
+/// `ui.theme` as written. Reading it touches no files; `ThemeSettings::resolve`
+/// loads the theme it names once the whole config is read.
+#[derive(Debug, Clone, Default)]
+struct ThemeSpec {
+    name: Option<String>,
+    light: Option<String>,
+    dark: Option<String>,
+    scheme: Option<(SchemeFormat, String)>,
+    colors: Option<PartialThemeColors>,
+    styles: Option<ThemeStyles>,
+    symbols: Option<ThemeSymbols>,
 }
 
 impl<'de> Deserialize<'de> for ThemeSettings {
@@ -76,7 +416,10 @@ impl<'de> Deserialize<'de> for ThemeSettings {
             where
                 E: de::Error,
             {
-                Ok(ThemeSettings::from_name(value))
+                Ok(ThemeSettings::from_spec(ThemeSpec {
+                    name: Some(value.to_string()),
+                    ..ThemeSpec::default()
+                }))
             }
 
             fn visit_map<M>(self, mut map: M) -> Result<ThemeSettings, M::Error>
@@ -84,33 +427,64 @@ impl<'de> Deserialize<'de> for ThemeSettings {
                 M: MapAccess<'de>,
             {
                 let mut name: Option<String> = None;
-                let mut colors: Option<ThemeColors> = None;
+                let mut light: Option<String> = None;
+                let mut dark: Option<String> = None;
+                let mut colors: Option<PartialThemeColors> = None;
+                let mut styles: Option<ThemeStyles> = None;
+                let mut symbols: Option<ThemeSymbols> = None;
+                let mut scheme: Option<(SchemeFormat, String)> = None;
 
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
                         "name" => {
-                            let theme_name: String = map.next_value()?;
-                            name = Some(theme_name.clone());
-                            // If only name is provided, use preset theme
-                            if colors.is_none() {
-                                let preset_theme = ThemeSettings::from_name(&theme_name);
-                                colors = Some(preset_theme.colors);
-                            }
+                            name = Some(map.next_value()?);
+                        }
+                        "light" => {
+                            light = Some(map.next_value()?);
+                        }
+                        "dark" => {
+                            dark = Some(map.next_value()?);
                         }
                         "colors" => {
                             colors = Some(map.next_value()?);
                         }
-                        _ => {
-                            // Ignore unknown fields
-                            let _: serde_yaml::Value = map.next_value()?;
+                        "styles" => {
+                            styles = Some(map.next_value()?);
+                        }
+                        "symbols" => {
+                            symbols = Some(map.next_value()?);
                         }
+                        key => match SchemeFormat::from_key(key) {
+                            // `base16: path` and the like
+                            Some(format) => scheme = Some((format, map.next_value()?)),
+                            None => {
+                                // Ignore unknown fields
+                                let _: serde_yaml::Value = map.next_value()?;
+                            }
+                        },
                     }
                 }
 
-                let name = name.unwrap_or_else(|| "catppuccin_mocha".to_string());
-                let colors = colors.unwrap_or_else(ThemeColors::catppuccin_mocha);
+                if let Some(colors) = &colors {
+                    colors
+                        .validate("colors")
+                        .map_err(|e| de::Error::custom(format!("{:#}", e)))?;
+                }
+                if let Some(styles) = &styles {
+                    styles
+                        .validate("styles")
+                        .map_err(|e| de::Error::custom(format!("{:#}", e)))?;
+                }
 
-                Ok(ThemeSettings { name, colors })
+                Ok(ThemeSettings::from_spec(ThemeSpec {
+                    name,
+                    light,
+                    dark,
+                    scheme,
+                    colors,
+                    styles,
+                    symbols,
+                }))
             }
         }
 
@@ -123,29 +497,212 @@ impl Default for ThemeSettings {
         Self {
             name: "catppuccin_mocha".to_string(),
             colors: ThemeColors::catppuccin_mocha(),
+            styles: ThemeStyles::default(),
+            symbols: ThemeSymbols::default(),
+            overrides: Vec::new(),
+            appearance: None,
+            files: Vec::new(),
+            spec: ThemeSpec::default(),
         }
     }
 }
 
 impl ThemeSettings {
-    pub fn from_name(name: &str) -> Self {
-        let colors = match name.to_lowercase().as_str() {
-            "catppuccin_mocha" | "mocha" => ThemeColors::catppuccin_mocha(),
-            "catppuccin_latte" | "latte" => ThemeColors::catppuccin_latte(),
-            "catppuccin_macchiato" | "macchiato" => ThemeColors::catppuccin_macchiato(),
-            "catppuccin_frappe" | "frappe" => ThemeColors::catppuccin_frappe(),
-            _ => {
-                eprintln!(
-                    "Warning: Unknown theme '{}', falling back to catppuccin_mocha",
-                    name
-                );
-                ThemeColors::catppuccin_mocha()
+    /// The default look until `resolve` loads the theme `spec` names.
+    fn from_spec(spec: ThemeSpec) -> Self {
+        Self {
+            name: spec.name.clone().unwrap_or_else(|| Self::default().name),
+            spec,
+            ..Self::default()
+        }
+    }
+
+    /// Whether `theme: { light, dark }` picks the theme by the background.
+    pub fn follows_appearance(&self) -> bool {
+        self.spec.light.is_some() || self.spec.dark.is_some()
+    }
+
+    /// Load the preset, theme file or imported scheme `ui.theme` names and
+    /// layer its `colors`, `styles` and `symbols` on top. `appearance` is
+    /// only asked for when the theme follows it. Returns a warning when the
+    /// theme isn't found and the default one is used instead.
+    pub fn resolve(&mut self, appearance: impl FnOnce() -> Detected) -> Result<Option<String>> {
+        let detected = self.follows_appearance().then(appearance);
+        let spec = std::mem::take(&mut self.spec);
+        let name = match detected.map(|detected| detected.appearance) {
+            Some(Appearance::Light) => spec.light.clone().or(spec.dark.clone()),
+            Some(Appearance::Dark) => spec.dark.clone().or(spec.light.clone()),
+            None => spec.name.clone(),
+        };
+
+        // The colors given are layered on the preset or imported scheme
+        let mut warning = None;
+        let mut theme = match &spec.scheme {
+            Some((format, path)) => {
+                let imported = themes::import(*format, path)?;
+                Self::from_loaded(name.unwrap_or_else(|| path.clone()), imported)
+            }
+            None => {
+                let name = name.as_deref().unwrap_or("catppuccin_mocha");
+                Self::from_name(name).unwrap_or_else(|e| {
+                    warning = Some(format!("{:#}, falling back to catppuccin_mocha", e));
+                    Self {
+                        name: name.to_string(),
+                        ..Self::default()
+                    }
+                })
             }
         };
+        theme.appearance = detected;
+        if let Some(colors) = spec.colors.clone() {
+            theme.overrides = colors.apply(&mut theme.colors);
+        }
+        if let Some(styles) = spec.styles.clone() {
+            theme.styles.merge(styles);
+        }
+        if let Some(symbols) = spec.symbols.clone() {
+            theme.symbols.merge(symbols);
+        }
+        theme.spec = spec;
+        *self = theme;
+        Ok(warning)
+    }
 
+    /// A preset, or a theme file from the theme directories.
+    pub fn from_name(name: &str) -> Result<Self> {
+        Ok(Self::from_loaded(name.to_string(), themes::load(name)?))
+    }
+
+    pub fn from_loaded(name: String, theme: themes::LoadedTheme) -> Self {
         Self {
-            name: name.to_string(),
-            colors,
+            name,
+            colors: theme.colors,
+            styles: theme.styles,
+            symbols: theme.symbols,
+            overrides: Vec::new(),
+            appearance: None,
+            files: theme.files,
+            spec: ThemeSpec::default(),
+        }
+    }
+}
+
+/// How one element is drawn, on top of the theme colors: `fg` and `bg` in
+/// any color syntax, and modifiers such as `bold` or `italic`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ElementStyle {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    /// Replaces the element's default modifiers; `[]` removes them.
+    pub modifiers: Option<Vec<String>>,
+}
+
+impl ElementStyle {
+    /// Take the parts `other` sets.
+    fn merge(&mut self, other: ElementStyle) {
+        if other.fg.is_some() {
+            self.fg = other.fg;
+        }
+        if other.bg.is_some() {
+            self.bg = other.bg;
+        }
+        if other.modifiers.is_some() {
+            self.modifiers = other.modifiers;
+        }
+    }
+}
+
+/// `theme.styles`: per-element styles, each layered over the element's
+/// default look in the theme colors.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeStyles {
+    pub key: Option<ElementStyle>,
+    /// The modifiers of a key combination, e.g. `SUPER + SHIFT + `.
+    pub modifier: Option<ElementStyle>,
+    pub separator: Option<ElementStyle>,
+    pub description: Option<ElementStyle>,
+    pub action: Option<ElementStyle>,
+    /// The selected tab.
+    pub category: Option<ElementStyle>,
+    /// Characters matching the search query.
+    pub matched: Option<ElementStyle>,
+    pub selected: Option<ElementStyle>,
+    /// Bind flags such as `[el]` for `bindel`.
+    pub flags: Option<ElementStyle>,
+}
+
+impl ThemeStyles {
+    fn elements_mut(&mut self) -> [(&'static str, &mut Option<ElementStyle>); 9] {
+        [
+            ("key", &mut self.key),
+            ("modifier", &mut self.modifier),
+            ("separator", &mut self.separator),
+            ("description", &mut self.description),
+            ("action", &mut self.action),
+            ("category", &mut self.category),
+            ("matched", &mut self.matched),
+            ("selected", &mut self.selected),
+            ("flags", &mut self.flags),
+        ]
+    }
+
+    /// Layer `other` over these styles, element by element.
+    pub(crate) fn merge(&mut self, mut other: ThemeStyles) {
+        for ((_, style), (_, other)) in self.elements_mut().into_iter().zip(other.elements_mut()) {
+            match (style.as_mut(), other.take()) {
+                (Some(style), Some(other)) => style.merge(other),
+                (None, other) => *style = other,
+                (Some(_), None) => {}
+            }
+        }
+    }
+
+    /// Check every color and modifier, naming the key of the first bad one,
+    /// e.g. `styles.key.modifiers`.
+    pub(crate) fn validate(&self, prefix: &str) -> Result<()> {
+        for (name, style) in self.clone().elements_mut() {
+            let Some(style) = style else {
+                continue;
+            };
+            for (part, value) in [("fg", &style.fg), ("bg", &style.bg)] {
+                if let Some(value) = value {
+                    crate::theme::parse_color(value).with_context(|| {
+                        format!("{}.{}.{}: invalid color `{}`", prefix, name, part, value)
+                    })?;
+                }
+            }
+            for modifier in style.modifiers.iter().flatten() {
+                crate::theme::parse_modifier(modifier).with_context(|| {
+                    format!(
+                        "{}.{}.modifiers: invalid modifier `{}`",
+                        prefix, name, modifier
+                    )
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `theme.symbols`: the strings drawn around the keybindings.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeSymbols {
+    /// In front of the selected row; `"> "` by default.
+    pub highlight: Option<String>,
+    /// Between the key and the description; `" → "` by default.
+    pub separator: Option<String>,
+}
+
+impl ThemeSymbols {
+    pub(crate) fn merge(&mut self, other: ThemeSymbols) {
+        if other.highlight.is_some() {
+            self.highlight = other.highlight;
+        }
+        if other.separator.is_some() {
+            self.separator = other.separator;
         }
     }
 }
@@ -166,6 +723,92 @@ pub struct ThemeColors {
     pub border_color: String,
 }
 
+/// `theme.colors` as written in the config: any subset of the colors, to
+/// override the preset's.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PartialThemeColors {
+    background: Option<String>,
+    foreground: Option<String>,
+    selected_bg: Option<String>,
+    selected_fg: Option<String>,
+    search_bg: Option<String>,
+    search_fg: Option<String>,
+    key_color: Option<String>,
+    action_color: Option<String>,
+    category_color: Option<String>,
+    description_color: Option<String>,
+    matched_color: Option<String>,
+    border_color: Option<String>,
+}
+
+impl PartialThemeColors {
+    /// Check that every given color parses, naming the key of the first one
+    /// that doesn't, e.g. `colors.key_color`.
+    pub(crate) fn validate(&self, prefix: &str) -> Result<()> {
+        let fields = [
+            ("background", &self.background),
+            ("foreground", &self.foreground),
+            ("selected_bg", &self.selected_bg),
+            ("selected_fg", &self.selected_fg),
+            ("search_bg", &self.search_bg),
+            ("search_fg", &self.search_fg),
+            ("key_color", &self.key_color),
+            ("action_color", &self.action_color),
+            ("category_color", &self.category_color),
+            ("description_color", &self.description_color),
+            ("matched_color", &self.matched_color),
+            ("border_color", &self.border_color),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                crate::theme::parse_color(value)
+                    .with_context(|| format!("{}.{}: invalid color `{}`", prefix, name, value))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the given colors on `colors`, returning their names.
+    pub(crate) fn apply(self, colors: &mut ThemeColors) -> Vec<String> {
+        let fields = [
+            ("background", self.background, &mut colors.background),
+            ("foreground", self.foreground, &mut colors.foreground),
+            ("selected_bg", self.selected_bg, &mut colors.selected_bg),
+            ("selected_fg", self.selected_fg, &mut colors.selected_fg),
+            ("search_bg", self.search_bg, &mut colors.search_bg),
+            ("search_fg", self.search_fg, &mut colors.search_fg),
+            ("key_color", self.key_color, &mut colors.key_color),
+            ("action_color", self.action_color, &mut colors.action_color),
+            (
+                "category_color",
+                self.category_color,
+                &mut colors.category_color,
+            ),
+            (
+                "description_color",
+                self.description_color,
+                &mut colors.description_color,
+            ),
+            (
+                "matched_color",
+                self.matched_color,
+                &mut colors.matched_color,
+            ),
+            ("border_color", self.border_color, &mut colors.border_color),
+        ];
+
+        let mut overridden = Vec::new();
+        for (name, value, color) in fields {
+            if let Some(value) = value {
+                *color = value;
+                overridden.push(name.to_string());
+            }
+        }
+        overridden
+    }
+}
+
 impl ThemeColors {
     pub fn catppuccin_mocha() -> Self {
         Self {
@@ -248,65 +891,481 @@ fn default_max_results() -> usize {
     50
 }
 
+/// The categories used when `showkey.yaml` has no `categories` section.
+fn default_categories() -> HashMap<String, Category> {
+    let categories: [(&str, &str, &str, &[&str]); 7] = [
+        (
+            "applications",
+            "Applications",
+            "Launch applications and tools",
+            &[
+                "terminal",
+                "browser",
+                "filemanager",
+                "calculator",
+                "rofi",
+                "fuzzel",
+                "wofi",
+                "launcher",
+            ],
+        ),
+        (
+            "windows",
+            "Window Management",
+            "Control windows and their layout",
+            &[
+                "killactive",
+                "fullscreen",
+                "togglefloating",
+                "togglesplit",
+                "togglegroup",
+                "swapsplit",
+                "pseudo",
+                "pin",
+            ],
+        ),
+        (
+            "focus",
+            "Window Focus & Movement",
+            "Move focus and swap windows",
+            &["movefocus", "swapwindow", "cyclenext", "bringactivetotop"],
+        ),
+        (
+            "resize",
+            "Window Resizing",
+            "Resize windows",
+            &["resizeactive", "movewindow", "resizewindow"],
+        ),
+        (
+            "workspaces",
+            "Workspace Management",
+            "Switch and manage workspaces",
+            &["workspace", "movetoworkspace"],
+        ),
+        (
+            "system",
+            "System Actions",
+            "System controls and utilities",
+            &[
+                "screenshot",
+                "grim",
+                "hyprshot",
+                "hyprctl",
+                "wlogout",
+                "hyprlock",
+                "reload",
+                "exit",
+            ],
+        ),
+        (
+            "media",
+            "Media Controls",
+            "Audio, brightness and media controls",
+            &[
+                "XF86Audio",
+                "XF86MonBrightness",
+                "brightnessctl",
+                "pactl",
+                "wpctl",
+                "playerctl",
+            ],
+        ),
+    ];
+
+    categories
+        .into_iter()
+        .map(|(id, name, description, keywords)| {
+            let category = Category {
+                name: name.to_string(),
+                description: description.to_string(),
+                keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            };
+            (id.to_string(), category)
+        })
+        .collect()
+}
+
+fn default_doc_comments() -> bool {
+    true
+}
+
+fn default_mouse() -> bool {
+    true
+}
+
+fn default_table_columns() -> Vec<TableColumn> {
+    vec![
+        TableColumn::Key,
+        TableColumn::Description,
+        TableColumn::Dispatcher,
+        TableColumn::Category,
+    ]
+}
+
+fn default_keyboard_layout() -> String {
+    "ansi".to_string()
+}
+
 impl Config {
-    pub fn load(config_path: Option<PathBuf>) -> Result<Self> {
-        let config_file = if let Some(path) = config_path {
-            path
-        } else {
-            // Try default locations
-            let mut default_path = dirs::config_dir().context("Could not find config directory")?;
-            default_path.push("hypr-showkey/showkey.yaml");
-
-            if !default_path.exists() {
-                // Also check current directory
-                let current_dir_config = PathBuf::from("showkey.yaml");
-                if current_dir_config.exists() {
-                    current_dir_config
-                } else {
-                    return Err(anyhow::anyhow!(
-                        "Configuration file not found. Please create ~/.config/hypr-showkey/showkey.yaml or provide a path with --config"
-                    ));
-                }
-            } else {
-                default_path
-            }
+    /// The directory relative `hyprland_configs.files` are resolved against.
+    fn base_dir(&self) -> Result<PathBuf> {
+        let hypr_config_dir = || -> Result<PathBuf> {
+            Ok(dirs::config_dir()
+                .context("Could not find config directory")?
+                .join("hypr"))
         };
+        let Some(base_dir) = &self.hyprland_configs.base_dir else {
+            return hypr_config_dir();
+        };
+
+        let base_dir = paths::expand(base_dir, |name| std::env::var(name).ok())
+            .context("Invalid `hyprland_configs.base_dir`")?;
+        let config_dir = self
+            .path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+        // `components` drops the `.` of `base_dir: "."`
+        Ok(config_dir.join(base_dir).components().collect())
+    }
+
+    /// Find the configuration file: the given path, or the default
+    /// location, or `showkey.yaml` in the current directory. `None` means
+    /// there is none and the defaults apply.
+    pub fn locate(config_path: Option<PathBuf>) -> Result<Option<PathBuf>> {
+        if let Some(path) = config_path {
+            return Ok(Some(path));
+        }
+
+        // Try default locations
+        let mut default_path = dirs::config_dir().context("Could not find config directory")?;
+        default_path.push("hypr-showkey/showkey.yaml");
+
+        if default_path.exists() {
+            return Ok(Some(default_path));
+        }
+
+        // Also check current directory
+        let current_dir_config = PathBuf::from("showkey.yaml");
+        Ok(current_dir_config.exists().then_some(current_dir_config))
+    }
 
-        let content = std::fs::read_to_string(&config_file)
+    /// Load the configuration file found by [`Config::locate`], or the
+    /// defaults without one. `appearance` picks the variant of
+    /// `theme: { light, dark }` and is only called for such a theme.
+    pub fn load(config_file: Option<&Path>, appearance: impl FnOnce() -> Detected) -> Result<Self> {
+        match config_file {
+            Some(config_file) => Self::load_from(config_file, appearance),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(config_file: &Path, appearance: impl FnOnce() -> Detected) -> Result<Self> {
+        let content = std::fs::read_to_string(config_file)
             .with_context(|| format!("Failed to read config file: {:?}", config_file))?;
 
-        let config: Config = serde_yaml::from_str(&content)
+        // A file with nothing but comments is an empty document
+        let config: Option<Config> = serde_yaml::from_str(&content)
             .with_context(|| format!("Failed to parse config file: {:?}", config_file))?;
 
+        let mut config = config.unwrap_or_default();
+        config.path = Some(config_file.to_path_buf());
+        let warning = config
+            .ui
+            .theme
+            .resolve(appearance)
+            .with_context(|| format!("Failed to load the theme in {:?}", config_file))?;
+        config.warnings.extend(warning);
         Ok(config)
     }
 
-    pub fn resolve_hyprland_paths(&self) -> Result<Vec<PathBuf>> {
+    /// The Hyprland files to read. Files that are skipped or missing are
+    /// added to `warnings`.
+    pub fn resolve_hyprland_paths(&self, warnings: &mut Vec<String>) -> Result<Vec<PathBuf>> {
+        if self.hyprland_configs.files.is_empty() {
+            return find_hyprland_config(|name| std::env::var(name).ok()).map(|path| vec![path]);
+        }
+
+        let env = |name: &str| std::env::var(name).ok();
         let mut resolved_paths = Vec::new();
-        let hypr_config_dir = dirs::config_dir()
-            .context("Could not find config directory")?
-            .join("hypr");
-
-        for file_path in &self.hyprland_configs.files {
-            let path = if file_path.starts_with('/') {
-                // Absolute path
-                PathBuf::from(file_path)
-            } else {
-                // Relative to ~/.config/hypr/
-                hypr_config_dir.join(file_path)
+        let base_dir = self.base_dir()?;
+
+        for file in &self.hyprland_configs.files {
+            let path = match paths::expand(file.path(), env) {
+                // Relative to the base directory, ~/.config/hypr/ by default
+                Ok(path) => base_dir.join(path),
+                Err(e) => {
+                    if !file.optional() {
+                        warnings.push(format!(
+                            "Skipping Hyprland config file {:?}: {}",
+                            file.path(),
+                            e
+                        ));
+                    }
+                    continue;
+                }
             };
 
-            if path.exists() {
+            if paths::is_glob(&path.to_string_lossy()) {
+                let matches = paths::glob(&path);
+                if matches.is_empty() && !file.optional() {
+                    warnings.push(format!("No Hyprland config files match {:?}", path));
+                }
+                resolved_paths.extend(matches);
+            } else if path.exists() {
                 resolved_paths.push(path);
-            } else {
-                eprintln!("Warning: Hyprland config file not found: {:?}", path);
+            } else if !file.optional() {
+                warnings.push(format!("Hyprland config file not found: {:?}", path));
             }
         }
 
         if resolved_paths.is_empty() {
-            return Err(anyhow::anyhow!("No valid Hyprland config files found"));
+            // Nothing else reports the warnings when there is nothing to read
+            let reasons: String = warnings.iter().map(|w| format!("\n  {}", w)).collect();
+            return Err(anyhow::anyhow!(
+                "No valid Hyprland config files found{}",
+                reasons
+            ));
         }
 
         Ok(resolved_paths)
     }
 }
+
+/// Hyprland's own config file, looked up the way Hyprland does: the
+/// `HYPRLAND_CONFIG` variable, then `hypr/hyprland.conf` in
+/// `$XDG_CONFIG_HOME`, `~/.config` and each of `$XDG_CONFIG_DIRS`.
+fn find_hyprland_config(env: impl Fn(&str) -> Option<String>) -> Result<PathBuf> {
+    if let Some(path) = env("HYPRLAND_CONFIG").filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    dirs.extend(
+        env("XDG_CONFIG_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from),
+    );
+    dirs.extend(env("HOME").map(|home| Path::new(&home).join(".config")));
+    let config_dirs = env("XDG_CONFIG_DIRS")
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/etc/xdg".to_string());
+    dirs.extend(
+        config_dirs
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from),
+    );
+
+    let candidates: Vec<PathBuf> = dirs
+        .iter()
+        .map(|dir| dir.join("hypr/hyprland.conf"))
+        .collect();
+    candidates
+        .iter()
+        .find(|path| path.exists())
+        .cloned()
+        .with_context(|| {
+            let tried: Vec<String> = candidates.iter().map(|p| p.display().to_string()).collect();
+            format!(
+                "No Hyprland config found (tried {}). Set HYPRLAND_CONFIG or list files under `hyprland_configs.files` in showkey.yaml",
+                tried.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::appearance;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn test_partial_colors_layer_on_preset() {
+        let mut theme: ThemeSettings = serde_yaml::from_str(
+            "colors:\n  key_color: \"#ff6b9d\"\n  border_color: \"#c6a0f6\"\nname: latte\n",
+        )
+        .unwrap();
+        theme.resolve(appearance::known).unwrap();
+        assert_eq!(theme.colors.key_color, "#ff6b9d");
+        assert_eq!(
+            theme.colors.background,
+            ThemeColors::catppuccin_latte().background
+        );
+        assert_eq!(theme.overrides, ["key_color", "border_color"]);
+
+        let error = serde_yaml::from_str::<ThemeSettings>("colors:\n  key_colour: \"#ff0000\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("unknown field `key_colour`"), "{}", error);
+
+        let error = serde_yaml::from_str::<ThemeSettings>("colors:\n  key_color: blurple\n")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("colors.key_color: invalid color `blurple`"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_light_and_dark_follow_the_appearance() {
+        let resolve = |yaml: &str, appearance: Appearance| {
+            let mut theme: ThemeSettings = serde_yaml::from_str(yaml).unwrap();
+            // Reading `light`/`dark` detects nothing; resolving asks once
+            assert!(theme.follows_appearance());
+            theme
+                .resolve(|| Detected {
+                    appearance,
+                    source: "test",
+                })
+                .unwrap();
+            theme
+        };
+        let both = "light: latte\ndark: frappe\n";
+        let theme = resolve(both, Appearance::Light);
+        assert_eq!(
+            theme.colors.background,
+            ThemeColors::catppuccin_latte().background
+        );
+        assert_eq!(theme.appearance.unwrap().source, "test");
+        let theme = resolve(both, Appearance::Dark);
+        assert_eq!(theme.name, "frappe");
+        // A missing variant falls back to the other one
+        assert_eq!(resolve("light: latte\n", Appearance::Dark).name, "latte");
+
+        let mut theme: ThemeSettings = serde_yaml::from_str("name: frappe\n").unwrap();
+        theme
+            .resolve(|| panic!("a named theme doesn't depend on the appearance"))
+            .unwrap();
+        assert_eq!(theme.appearance, None);
+    }
+
+    #[test]
+    fn test_hyprland_config_lookup_order() {
+        let dir = TempDir::new("lookup");
+        for sub in ["home/.config/hypr", "xdg/hypr"] {
+            dir.write(&format!("{}/hyprland.conf", sub), "");
+        }
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            let dir = dir.path().to_path_buf();
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.replace("DIR", dir.to_str().unwrap()))
+            }
+        };
+
+        // $XDG_CONFIG_HOME/hypr is missing, so ~/.config comes next
+        let found = find_hyprland_config(vars(&[
+            ("XDG_CONFIG_HOME", "DIR/missing"),
+            ("HOME", "DIR/home"),
+        ]));
+        assert_eq!(found.unwrap(), dir.join("home/.config/hypr/hyprland.conf"));
+
+        let found = find_hyprland_config(vars(&[("XDG_CONFIG_DIRS", "DIR/none:DIR/xdg")]));
+        assert_eq!(found.unwrap(), dir.join("xdg/hypr/hyprland.conf"));
+
+        let found = find_hyprland_config(vars(&[
+            ("HYPRLAND_CONFIG", "/custom.conf"),
+            ("HOME", "DIR/home"),
+        ]));
+        assert_eq!(found.unwrap(), PathBuf::from("/custom.conf"));
+
+        assert!(find_hyprland_config(vars(&[("XDG_CONFIG_DIRS", "DIR/none")])).is_err());
+        let found = find_hyprland_config(vars(&[
+            ("HYPRLAND_CONFIG", ""),
+            ("XDG_CONFIG_HOME", ""),
+            ("HOME", "DIR/home"),
+        ]));
+        assert_eq!(found.unwrap(), dir.join("home/.config/hypr/hyprland.conf"));
+    }
+
+    #[test]
+    fn test_files_resolve_against_the_base_dir() {
+        let dir = TempDir::new("resolve");
+        for file in ["hypr/binds.conf", "hypr/apps/b.conf", "hypr/apps/a.conf"] {
+            dir.write(file, "");
+        }
+        let config_file = dir.write(
+            "showkey.yaml",
+            r#"hyprland_configs:
+  base_dir: hypr
+  files:
+    - apps/*.conf
+    - binds.conf
+    - missing.conf
+    - { path: local.conf, optional: true }
+    - { path: "$HYPR_SHOWKEY_UNSET/x.conf", optional: true }
+    - $HYPR_SHOWKEY_UNSET/y.conf
+    - none/*.conf
+"#,
+        );
+        let config = Config::load_from(&config_file, appearance::known).unwrap();
+
+        let mut warnings = Vec::new();
+        let paths = config.resolve_hyprland_paths(&mut warnings).unwrap();
+        assert_eq!(
+            paths,
+            [
+                dir.join("hypr/apps/a.conf"),
+                dir.join("hypr/apps/b.conf"),
+                dir.join("hypr/binds.conf"),
+            ]
+        );
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings[0].starts_with("Hyprland config file not found"));
+        assert!(warnings[0].contains("missing.conf"));
+        assert!(warnings[1].contains("`HYPR_SHOWKEY_UNSET` is not set"));
+        assert!(warnings[2].starts_with("No Hyprland config files match"));
+
+        // Without base_dir, relative paths are relative to ~/.config/hypr
+        let config = Config {
+            path: Some(config_file),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.base_dir().unwrap(),
+            dirs::config_dir().unwrap().join("hypr")
+        );
+
+        // Nothing to read is an error that carries the warnings
+        let config_file = dir.write(
+            "gone.yaml",
+            "hyprland_configs:\n  base_dir: .\n  files: [gone.conf]\n",
+        );
+        let config = Config::load_from(&config_file, appearance::known).unwrap();
+        assert_eq!(config.base_dir().unwrap(), dir.path());
+        let error = config
+            .resolve_hyprland_paths(&mut Vec::new())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("gone.conf"), "{}", error);
+    }
+
+    #[test]
+    fn test_every_section_is_optional() {
+        let defaults = Config::load(None, appearance::known).unwrap();
+        assert!(defaults.path.is_none());
+        assert!(defaults.hyprland_configs.files.is_empty());
+        assert!(defaults.hyprland_configs.doc_comments);
+        assert_eq!(defaults.categories.len(), default_categories().len());
+
+        let dir = TempDir::new("optional");
+        let empty = dir.write("empty.yaml", "# nothing configured yet\n");
+        let config = Config::load_from(&empty, appearance::known).unwrap();
+        assert_eq!(config.path.as_deref(), Some(empty.as_path()));
+        assert!(config.hyprland_configs.files.is_empty());
+        assert_eq!(config.categories.len(), default_categories().len());
+        assert!(config.ui.mouse);
+
+        // One section leaves the others at their defaults
+        let partial = dir.write("partial.yaml", "ui:\n  mouse: false\n");
+        let config = Config::load_from(&partial, appearance::known).unwrap();
+        assert!(!config.ui.mouse);
+        assert!(config.hyprland_configs.files.is_empty());
+        assert!(config.hyprland_configs.base_dir.is_none());
+        assert!(config.categories.contains_key("workspaces"));
+    }
+}
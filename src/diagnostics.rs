@@ -0,0 +1,207 @@
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    /// ANSI escape for the label: bold red or bold yellow, like rustc.
+    fn ansi(self) -> &'static str {
+        match self {
+            Severity::Error => "\x1b[1;31m",
+            Severity::Warning => "\x1b[1;33m",
+        }
+    }
+}
+
+/// A position in a Hyprland config file. Lines and columns start at 1;
+/// columns count characters, not bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Location {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.column)
+    }
+}
+
+/// A problem found while reading the Hyprland configuration.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub location: Location,
+    /// Width of the highlighted span, in characters.
+    pub len: usize,
+    /// The text of the offending line, shown under the message.
+    pub source_line: String,
+    pub note: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, location: Location, len: usize, line: &str) -> Self {
+        Self::new(Severity::Error, message.into(), location, len, line)
+    }
+
+    pub fn warning(message: impl Into<String>, location: Location, len: usize, line: &str) -> Self {
+        Self::new(Severity::Warning, message.into(), location, len, line)
+    }
+
+    fn new(
+        severity: Severity,
+        message: String,
+        location: Location,
+        len: usize,
+        line: &str,
+    ) -> Self {
+        Self {
+            severity,
+            message,
+            location,
+            len: len.max(1),
+            source_line: line.to_string(),
+            note: None,
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// `file:line:column: severity: message`, for one-line displays.
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {}: {}",
+            self.location,
+            self.severity.label(),
+            self.message
+        )
+    }
+
+    /// Render the diagnostic the way rustc does, with the source line and
+    /// a caret under the offending span.
+    pub fn render(&self, color: bool) -> String {
+        let (label, bold, blue, reset) = if color {
+            (self.severity.ansi(), "\x1b[1m", "\x1b[1;34m", "\x1b[0m")
+        } else {
+            ("", "", "", "")
+        };
+        let line_number = self.location.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        // Keep tabs before the span so the carets line up with the text
+        let padding: String = self
+            .source_line
+            .chars()
+            .take(self.location.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let mut out = format!(
+            "{label}{}{reset}{bold}: {}{reset}\n\
+             {gutter}{blue}-->{reset} {}\n\
+             {gutter} {blue}|{reset}\n\
+             {blue}{line_number} |{reset} {}\n\
+             {gutter} {blue}|{reset} {padding}{label}{}{reset}\n",
+            self.severity.label(),
+            self.message,
+            self.location,
+            self.source_line,
+            "^".repeat(self.len),
+        );
+        if let Some(note) = &self.note {
+            out.push_str(&format!(
+                "{gutter} {blue}={reset} {bold}note{reset}: {note}\n"
+            ));
+        }
+        out
+    }
+}
+
+/// "2 errors, 1 warning", or `None` when there is nothing to report.
+pub fn count_summary(diagnostics: &[Diagnostic]) -> Option<String> {
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+
+    match (errors, warnings) {
+        (0, 0) => None,
+        (e, 0) => Some(plural(e, "error")),
+        (0, w) => Some(plural(w, "warning")),
+        (e, w) => Some(format!("{}, {}", plural(e, "error"), plural(w, "warning"))),
+    }
+}
+
+/// Whether `check` should exit non-zero: on any error, and on warnings
+/// too with `--deny-warnings`.
+pub fn check_fails(diagnostics: &[Diagnostic], deny_warnings: bool) -> bool {
+    diagnostics
+        .iter()
+        .any(|d| d.severity == Severity::Error || deny_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_points_at_column() {
+        let location = Location {
+            file: PathBuf::from("binds.conf"),
+            line: 12,
+            column: 18,
+        };
+        let diagnostic = Diagnostic::warning(
+            "unknown dispatcher `killactiv`",
+            location,
+            9,
+            "bind = SUPER, Q, killactiv",
+        );
+
+        assert_eq!(
+            diagnostic.render(false),
+            "warning: unknown dispatcher `killactiv`\n  \
+             --> binds.conf:12:18\n   \
+             |\n\
+             12 | bind = SUPER, Q, killactiv\n   \
+             |                  ^^^^^^^^^\n"
+        );
+        assert_eq!(
+            count_summary(std::slice::from_ref(&diagnostic)).as_deref(),
+            Some("1 warning")
+        );
+    }
+
+    #[test]
+    fn test_check_fails_on_errors_or_denied_warnings() {
+        let warning = Diagnostic::warning("w", Location::default(), 1, "");
+        let error = Diagnostic::error("e", Location::default(), 1, "");
+
+        assert!(!check_fails(&[], true));
+        assert!(!check_fails(std::slice::from_ref(&warning), false));
+        assert!(check_fails(std::slice::from_ref(&warning), true));
+        assert!(check_fails(&[warning.clone(), error.clone()], false));
+        assert_eq!(
+            count_summary(&[error.clone(), error, warning]).as_deref(),
+            Some("2 errors, 1 warning")
+        );
+    }
+}
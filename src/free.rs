@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+
+use crate::parser::{normalize_key, Keybinding};
+
+/// The keysyms checked by the free key finder, grouped for display.
+pub const KEY_GROUPS: &[(&str, &[&str])] = &[
+    (
+        "Letters",
+        &[
+            "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q",
+            "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
+        ],
+    ),
+    (
+        "Digits",
+        &["1", "2", "3", "4", "5", "6", "7", "8", "9", "0"],
+    ),
+    (
+        "Function",
+        &[
+            "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12",
+        ],
+    ),
+    (
+        "Punctuation",
+        &[
+            "grave",
+            "minus",
+            "equal",
+            "bracketleft",
+            "bracketright",
+            "backslash",
+            "semicolon",
+            "apostrophe",
+            "comma",
+            "period",
+            "slash",
+        ],
+    ),
+    (
+        "Special",
+        &[
+            "Return",
+            "space",
+            "Tab",
+            "BackSpace",
+            "Escape",
+            "Delete",
+            "Insert",
+            "Home",
+            "End",
+            "Prior",
+            "Next",
+            "Left",
+            "Right",
+            "Up",
+            "Down",
+            "Print",
+        ],
+    ),
+    (
+        "Media",
+        &[
+            "XF86AudioRaiseVolume",
+            "XF86AudioLowerVolume",
+            "XF86AudioMute",
+            "XF86AudioMicMute",
+            "XF86AudioPlay",
+            "XF86AudioPause",
+            "XF86AudioStop",
+            "XF86AudioNext",
+            "XF86AudioPrev",
+            "XF86MonBrightnessUp",
+            "XF86MonBrightnessDown",
+            "XF86Calculator",
+            "XF86Mail",
+            "XF86Search",
+            "XF86Explorer",
+            "XF86HomePage",
+            "XF86Sleep",
+            "XF86PowerOff",
+        ],
+    ),
+];
+
+/// Result of looking for unbound chords under one modifier set.
+#[derive(Debug, Default)]
+pub struct FreeKeys<'a> {
+    /// Free keysyms per group, in `KEY_GROUPS` order.
+    pub free: Vec<(&'static str, Vec<&'static str>)>,
+    /// Chords bound more than once with the same flags, keyed by normalized
+    /// key name and flags, as `hypr-showkey check` reports them.
+    pub conflicts: BTreeMap<(String, String), Vec<&'a Keybinding>>,
+}
+
+impl FreeKeys<'_> {
+    pub fn free_count(&self) -> usize {
+        self.free.iter().map(|(_, keys)| keys.len()).sum()
+    }
+
+    /// A plain-text report, one group per line.
+    pub fn report(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .free
+            .iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(group, keys)| format!("{:<12} {}", format!("{}:", group), keys.join(" ")))
+            .collect();
+
+        if !self.conflicts.is_empty() {
+            lines.push(String::new());
+            lines.push("Bound more than once:".to_string());
+            for bindings in self.conflicts.values() {
+                for kb in bindings {
+                    lines.push(format!("  {} → {}", kb.key, kb.action));
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+/// Find the keys with no binding under exactly `modifiers` (normalized and
+/// sorted, see `parse_modifiers`) in `submap` (`None` for the global map).
+/// Bindings removed by `unbind` are already gone from `keybindings`, and
+/// inactive ones don't count. A key bound with any flags isn't free, but
+/// only binds with the same flags conflict (`bind` and `bindr` on one key
+/// run on press and on release).
+pub fn find_free_keys<'a>(
+    keybindings: &'a [Keybinding],
+    modifiers: &[String],
+    submap: Option<&str>,
+) -> FreeKeys<'a> {
+    let mut used: BTreeMap<(String, String), Vec<&Keybinding>> = BTreeMap::new();
+    for kb in keybindings {
+        if !kb.inactive && kb.submap.as_deref() == submap && kb.modifiers() == modifiers {
+            used.entry((kb.key_name(), kb.flags.clone()))
+                .or_default()
+                .push(kb);
+        }
+    }
+
+    let free = KEY_GROUPS
+        .iter()
+        .map(|(group, keys)| {
+            let free_keys = keys
+                .iter()
+                .copied()
+                .filter(|key| {
+                    let key = normalize_key(key);
+                    !used.keys().any(|(name, _)| *name == key)
+                })
+                .collect();
+            (*group, free_keys)
+        })
+        .collect();
+
+    used.retain(|_, bindings| bindings.len() > 1);
+
+    FreeKeys {
+        free,
+        conflicts: used,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser::{parse_modifiers, HyprlandParser};
+    use crate::testutil::TempDir;
+
+    fn binding(key: &str, submap: Option<&str>) -> Keybinding {
+        flagged(key, submap, "")
+    }
+
+    fn flagged(key: &str, submap: Option<&str>, flags: &str) -> Keybinding {
+        Keybinding {
+            key: key.to_string(),
+            action: "exec, kitty".to_string(),
+            description: String::new(),
+            category: "Other".to_string(),
+            raw_command: String::new(),
+            submap: submap.map(str::to_string),
+            flags: flags.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_free_keys_respect_modifiers_and_submap() {
+        let keybindings = vec![
+            binding("Super + Q", None),
+            binding("Super + Shift + W", None),
+            binding("Super + E", Some("resize")),
+            binding("Super + code:10", None),
+        ];
+        let result = find_free_keys(&keybindings, &parse_modifiers("SUPER"), None);
+
+        let letters = &result.free[0].1;
+        assert!(!letters.contains(&"Q"));
+        assert!(letters.contains(&"W"));
+        assert!(letters.contains(&"E"));
+        assert!(!result.free[1].1.contains(&"1"));
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_conflicts_are_reported() {
+        let keybindings = vec![binding("SUPER + Q", None), binding("$mainMod + q", None)];
+        let result = find_free_keys(&keybindings, &parse_modifiers("super"), None);
+        assert_eq!(result.conflicts[&("Q".to_string(), String::new())].len(), 2);
+    }
+
+    #[test]
+    fn test_conflicts_need_the_same_flags() {
+        let keybindings = vec![
+            binding("SUPER + Q", None),
+            flagged("SUPER + Q", None, "r"),
+            flagged("SUPER + W", None, "l"),
+            flagged("SUPER + W", None, "l"),
+        ];
+        let result = find_free_keys(&keybindings, &parse_modifiers("SUPER"), None);
+        assert!(!result.free[0].1.contains(&"Q"));
+        assert_eq!(
+            result.conflicts.keys().collect::<Vec<_>>(),
+            [&("W".to_string(), "l".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_unbound_and_inactive_keys_are_free() {
+        let dir = TempDir::new("free");
+        let path = dir.write(
+            "hyprland.conf",
+            "bind = SUPER, Q, killactive\n\
+             bind = SUPER, W, exec, firefox\n\
+             unbind = SUPER, W\n\
+             # hyprlang if NOPE\n\
+             bind = SUPER, E, exec, kitty\n\
+             # hyprlang endif\n\
+             bind = SUPER, XF86AudioMute, exec, pamixer -t\n\
+             submap = resize\n\
+             bind = SUPER, R, exec, foo\n\
+             submap = reset\n",
+        );
+        let config: Config =
+            serde_yaml::from_str(&format!("hyprland_configs:\n  files: [{:?}]\n", path)).unwrap();
+        let output = HyprlandParser::new(&config).parse().unwrap();
+        let super_only = parse_modifiers("SUPER");
+
+        let result = find_free_keys(&output.keybindings, &super_only, None);
+        let free = |group: &str| {
+            result
+                .free
+                .iter()
+                .find(|(name, _)| *name == group)
+                .unwrap()
+                .1
+                .clone()
+        };
+        assert!(!free("Letters").contains(&"Q"));
+        assert!(free("Letters").contains(&"W"));
+        assert!(free("Letters").contains(&"E"));
+        assert!(free("Letters").contains(&"R"));
+        assert!(!free("Media").contains(&"XF86AudioMute"));
+
+        let resize = find_free_keys(&output.keybindings, &super_only, Some("resize"));
+        assert!(!resize.free[0].1.contains(&"R"));
+        assert!(resize.free[0].1.contains(&"Q"));
+        assert_eq!(
+            resize.free_count(),
+            KEY_GROUPS.iter().map(|(_, keys)| keys.len()).sum::<usize>() - 1
+        );
+    }
+
+    #[test]
+    fn test_report_lists_groups_and_conflicts() {
+        let keybindings = vec![binding("SUPER + Q", None), binding("SUPER + q", None)];
+        let mut result = find_free_keys(&keybindings, &parse_modifiers("SUPER"), None);
+        for (group, keys) in &mut result.free {
+            if *group != "Digits" {
+                keys.clear();
+            }
+        }
+        assert_eq!(
+            result.report(),
+            [
+                "Digits:      1 2 3 4 5 6 7 8 9 0",
+                "",
+                "Bound more than once:",
+                "  SUPER + Q → exec, kitty",
+                "  SUPER + q → exec, kitty",
+            ]
+        );
+    }
+}
@@ -0,0 +1,549 @@
+//! A parser for hyprlang, the configuration language of Hyprland.
+//!
+//! The parser only builds a syntax tree; it doesn't know what the keywords
+//! mean. Variables are not expanded and `##` escapes are kept in the raw
+//! values so that columns still point into the source line.
+
+use std::fmt;
+
+/// Keywords that may appear any number of times, as opposed to options
+/// that are assigned a single value. Any `bind` variant is a keyword too.
+const KEYWORDS: &[&str] = &[
+    "unbind",
+    "submap",
+    "exec",
+    "execr",
+    "exec-once",
+    "execr-once",
+    "exec-shutdown",
+    "monitor",
+    "workspace",
+    "windowrule",
+    "windowrulev2",
+    "layerrule",
+    "env",
+    "envd",
+    "animation",
+    "bezier",
+    "blurls",
+    "plugin",
+    "permission",
+    "gesture",
+];
+
+/// A comment, without the leading `#`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub text: String,
+    pub line: usize,
+}
+
+/// A `key = value` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The key as written, e.g. `bindel` or `gaps_in`; without the `$` for
+    /// variables.
+    pub key: String,
+    /// The value with comments removed and `##` escapes kept. See
+    /// `Entry::value`.
+    pub raw_value: String,
+    /// Line number of the (first line of the) entry.
+    pub line: usize,
+    pub key_column: usize,
+    pub value_column: usize,
+    /// The whole logical line, with continuations joined.
+    pub source_line: String,
+    /// The comment lines directly above the entry.
+    pub doc: Vec<Comment>,
+    /// A comment at the end of the line.
+    pub comment: Option<String>,
+}
+
+impl Entry {
+    /// The value with `##` unescaped.
+    pub fn value(&self) -> String {
+        unescape(&self.raw_value)
+    }
+
+    /// The column of a byte offset into `raw_value`.
+    pub fn value_column_at(&self, offset: usize) -> usize {
+        let offset = offset.min(self.raw_value.len());
+        self.value_column + self.raw_value[..offset].chars().count()
+    }
+}
+
+/// A `name { ... }` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub name: String,
+    pub line: usize,
+    pub doc: Vec<Comment>,
+    pub body: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// A comment that isn't directly above an entry.
+    Comment(Comment),
+    /// A `# hyprlang ...` directive comment.
+    Directive {
+        directive: Directive,
+        line: usize,
+    },
+    /// `$name = value`
+    Variable(Entry),
+    /// A repeatable keyword such as `bind` or `exec-once`.
+    Keyword(Entry),
+    /// `source = path`
+    Source(Entry),
+    /// An option, e.g. `general:gaps_in = 5` or `gaps_in = 5` inside a
+    /// `general { }` block. The key is the full path in the latter case.
+    Assignment(Entry),
+    Category(Category),
+}
+
+/// The `# hyprlang` directives that control how the lines after them are
+/// read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directive {
+    /// `# hyprlang if VAR` or `# hyprlang if !VAR`
+    If { variable: String, negated: bool },
+    /// `# hyprlang endif`
+    EndIf,
+    /// `# hyprlang noerror true|false`
+    NoError(bool),
+}
+
+impl fmt::Display for Directive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Directive::If { variable, negated } => {
+                write!(f, "if {}{}", if *negated { "!" } else { "" }, variable)
+            }
+            Directive::EndIf => write!(f, "endif"),
+            Directive::NoError(on) => write!(f, "noerror {}", on),
+        }
+    }
+}
+
+/// Parse the text of a comment as a directive. Returns `None` for ordinary
+/// comments and `Some(Err(message))` for malformed directives.
+fn parse_directive(comment: &str) -> Option<Result<Directive, String>> {
+    let rest = comment.strip_prefix("hyprlang")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    Some(match words[..] {
+        ["if", variable] => {
+            let (variable, negated) = match variable.strip_prefix('!') {
+                Some(variable) => (variable, true),
+                None => (variable, false),
+            };
+            Ok(Directive::If {
+                variable: variable.trim_start_matches('$').to_string(),
+                negated,
+            })
+        }
+        ["endif"] => Ok(Directive::EndIf),
+        ["noerror", "true"] => Ok(Directive::NoError(true)),
+        ["noerror", "false"] => Ok(Directive::NoError(false)),
+        _ => Err(format!("unknown directive `# hyprlang{}`", rest.trim_end())),
+    })
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Document {
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+    pub source_line: String,
+}
+
+/// Whether `key` is a keyword rather than an option.
+pub fn is_keyword(key: &str) -> bool {
+    KEYWORDS.contains(&key)
+        || key
+            .strip_prefix("bind")
+            .is_some_and(|flags| flags.chars().all(|c| c.is_ascii_lowercase()))
+}
+
+/// Replace `##` escapes with `#`.
+pub fn unescape(text: &str) -> String {
+    text.replace("##", "#")
+}
+
+/// Split a line into its code and its comment, if any.
+///
+/// A `#` starts a comment at the start of the line or after whitespace,
+/// outside of quotes, so `notify-send "#1"` and `bgcolor:#ff0000` keep
+/// their `#`. Elsewhere `##` is a literal `#`.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    // Whole-line comments are comments even when they start with `##`
+    let code_start = line.len() - line.trim_start().len();
+    if let Some(comment) = line[code_start..].strip_prefix('#') {
+        return (&line[..code_start], Some(comment.trim()));
+    }
+
+    let mut quote: Option<char> = None;
+    let mut previous: Option<char> = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '#' if chars.peek().is_some_and(|&(_, next)| next == '#') => {
+                chars.next();
+            }
+            '#' if quote.is_none() && previous.is_none_or(char::is_whitespace) => {
+                return (&line[..offset], Some(line[offset + 1..].trim()));
+            }
+            '"' if quote.is_none() => quote = Some('"'),
+            // An apostrophe only opens a quote where a shell word starts
+            '\'' if quote.is_none()
+                && previous.is_none_or(|p| p.is_whitespace() || "=,(".contains(p)) =>
+            {
+                quote = Some('\'')
+            }
+            c if quote == Some(c) => quote = None,
+            _ => {}
+        }
+        previous = Some(c);
+    }
+
+    (line, None)
+}
+
+/// Category names and keys are made of letters, digits, `_`, `-`, `.`
+/// and `:` (for `general:gaps_in = 5`).
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.:".contains(c))
+}
+
+fn column_at(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+struct Parser {
+    /// Open categories, innermost last.
+    stack: Vec<Category>,
+    root: Vec<Node>,
+    /// Comment lines waiting to be attached to the next entry.
+    doc: Vec<Comment>,
+    errors: Vec<SyntaxError>,
+}
+
+impl Parser {
+    fn nodes(&mut self) -> &mut Vec<Node> {
+        match self.stack.last_mut() {
+            Some(category) => &mut category.body,
+            None => &mut self.root,
+        }
+    }
+
+    /// Turn pending comments into standalone comment nodes.
+    fn flush_doc(&mut self) {
+        let doc = std::mem::take(&mut self.doc);
+        self.nodes().extend(doc.into_iter().map(Node::Comment));
+    }
+
+    fn close_category(&mut self) {
+        self.flush_doc();
+        if let Some(category) = self.stack.pop() {
+            self.nodes().push(Node::Category(category));
+        }
+    }
+
+    fn error(
+        &mut self,
+        message: impl Into<String>,
+        line: usize,
+        column: usize,
+        len: usize,
+        source: &str,
+    ) {
+        self.errors.push(SyntaxError {
+            message: message.into(),
+            line,
+            column,
+            len: len.max(1),
+            source_line: source.to_string(),
+        });
+    }
+
+    fn statement(&mut self, line: usize, source: &str, code: &str, comment: Option<&str>) {
+        let trimmed = code.trim();
+        let start = code.len() - code.trim_start().len();
+
+        if trimmed == "}" {
+            if self.stack.is_empty() {
+                self.error("unmatched `}`", line, column_at(source, start), 1, source);
+            }
+            self.close_category();
+            return;
+        }
+
+        if let Some(name) = trimmed.strip_suffix('{') {
+            let name = name.trim();
+            if !is_identifier(name) {
+                let len = name.chars().count();
+                self.error(
+                    "invalid category name",
+                    line,
+                    column_at(source, start),
+                    len,
+                    source,
+                );
+            }
+            let doc = std::mem::take(&mut self.doc);
+            self.stack.push(Category {
+                name: name.to_string(),
+                line,
+                doc,
+                body: Vec::new(),
+            });
+            return;
+        }
+
+        let Some(equals) = code.find('=') else {
+            self.error(
+                "expected `key = value`, `category {` or `}`",
+                line,
+                column_at(source, start),
+                trimmed.chars().count(),
+                source,
+            );
+            self.flush_doc();
+            return;
+        };
+
+        let key = code[..equals].trim();
+        let raw_value = code[equals + 1..].trim();
+        let value_start =
+            equals + 1 + (code[equals + 1..].len() - code[equals + 1..].trim_start().len());
+        let variable = key.strip_prefix('$');
+        if !is_identifier(variable.unwrap_or(key)) {
+            self.error(
+                "invalid key",
+                line,
+                column_at(source, start),
+                key.chars().count(),
+                source,
+            );
+            self.flush_doc();
+            return;
+        }
+
+        let path: Vec<&str> = self.stack.iter().map(|c| c.name.as_str()).collect();
+        let mut entry = Entry {
+            key: variable.unwrap_or(key).to_string(),
+            raw_value: raw_value.to_string(),
+            line,
+            key_column: column_at(source, start),
+            value_column: column_at(source, value_start),
+            source_line: source.to_string(),
+            doc: std::mem::take(&mut self.doc),
+            comment: comment.filter(|c| !c.is_empty()).map(str::to_string),
+        };
+
+        let node = if variable.is_some() {
+            Node::Variable(entry)
+        } else if !path.is_empty() {
+            entry.key = format!("{}:{}", path.join(":"), entry.key);
+            Node::Assignment(entry)
+        } else if key == "source" {
+            Node::Source(entry)
+        } else if is_keyword(key) {
+            Node::Keyword(entry)
+        } else {
+            Node::Assignment(entry)
+        };
+        self.nodes().push(node);
+    }
+}
+
+/// Parse a hyprlang file. Lines with syntax errors are reported and left
+/// out of the document; the rest of the file is still parsed.
+pub fn parse(text: &str) -> (Document, Vec<SyntaxError>) {
+    let mut parser = Parser {
+        stack: Vec::new(),
+        root: Vec::new(),
+        doc: Vec::new(),
+        errors: Vec::new(),
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = index + 1;
+        let mut source = lines[index].to_string();
+        index += 1;
+
+        // A trailing backslash continues the line
+        while index < lines.len() && split_comment(&source).1.is_none() {
+            let Some(stripped) = source.trim_end().strip_suffix('\\') else {
+                break;
+            };
+            source = format!("{}{}", stripped, lines[index].trim_start());
+            index += 1;
+        }
+
+        let (code, comment) = split_comment(&source);
+        if code.trim().is_empty() {
+            match comment {
+                Some(text) => match parse_directive(text) {
+                    Some(Ok(directive)) => {
+                        parser.flush_doc();
+                        parser.nodes().push(Node::Directive { directive, line });
+                    }
+                    Some(Err(message)) => {
+                        let column = source.len() - source.trim_start().len() + 1;
+                        let len = source.trim().chars().count();
+                        parser.error(message, line, column, len, &source);
+                    }
+                    None => parser.doc.push(Comment {
+                        text: text.to_string(),
+                        line,
+                    }),
+                },
+                // A blank line detaches the comments above it
+                None => parser.flush_doc(),
+            }
+            continue;
+        }
+
+        let code = code.to_string();
+        parser.statement(line, &source, &code, comment);
+    }
+
+    while let Some(category) = parser.stack.last() {
+        let (name, line) = (category.name.clone(), category.line);
+        let source = lines.get(line - 1).copied().unwrap_or_default();
+        let column = source.len() - source.trim_start().len() + 1;
+        parser.error(
+            format!("unclosed category `{}`", name),
+            line,
+            column,
+            name.chars().count(),
+            source,
+        );
+        parser.close_category();
+    }
+    parser.flush_doc();
+
+    (Document { nodes: parser.root }, parser.errors)
+}
+
+/// An indented outline of the tree, used by the golden-file tests.
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn entry(f: &mut fmt::Formatter<'_>, indent: &str, kind: &str, e: &Entry) -> fmt::Result {
+            for doc in &e.doc {
+                writeln!(f, "{}doc {}: {}", indent, doc.line, doc.text)?;
+            }
+            write!(
+                f,
+                "{}{} {}:{}: {} = {:?}",
+                indent, kind, e.line, e.key_column, e.key, e.raw_value
+            )?;
+            if let Some(comment) = &e.comment {
+                write!(f, " # {:?}", comment)?;
+            }
+            writeln!(f)
+        }
+
+        fn write_nodes(f: &mut fmt::Formatter<'_>, indent: &str, nodes: &[Node]) -> fmt::Result {
+            for node in nodes {
+                match node {
+                    Node::Comment(c) => writeln!(f, "{}comment {}: {}", indent, c.line, c.text)?,
+                    Node::Directive { directive, line } => {
+                        writeln!(f, "{}directive {}: {}", indent, line, directive)?
+                    }
+                    Node::Variable(e) => entry(f, indent, "variable", e)?,
+                    Node::Keyword(e) => entry(f, indent, "keyword", e)?,
+                    Node::Source(e) => entry(f, indent, "source", e)?,
+                    Node::Assignment(e) => entry(f, indent, "option", e)?,
+                    Node::Category(c) => {
+                        for doc in &c.doc {
+                            writeln!(f, "{}doc {}: {}", indent, doc.line, doc.text)?;
+                        }
+                        writeln!(f, "{}category {}: {}", indent, c.line, c.name)?;
+                        write_nodes(f, &format!("{}  ", indent), &c.body)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        write_nodes(f, "", &self.nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    #[test]
+    fn test_comments_respect_quotes_and_escapes() {
+        let (document, errors) = parse(
+            "bind = SUPER, N, exec, notify-send \"#1\" 'issue #2' # a comment\n\
+             $accent = ##ff0000\n\
+             bind = , X, exec, notify-send -h string:bgcolor:#ff0000\n",
+        );
+        assert!(errors.is_empty());
+
+        let Node::Keyword(bind) = &document.nodes[0] else {
+            panic!("expected a keyword");
+        };
+        assert_eq!(
+            bind.raw_value,
+            "SUPER, N, exec, notify-send \"#1\" 'issue #2'"
+        );
+        assert_eq!(bind.comment.as_deref(), Some("a comment"));
+
+        let Node::Variable(accent) = &document.nodes[1] else {
+            panic!("expected a variable");
+        };
+        assert_eq!(accent.value(), "#ff0000");
+
+        let Node::Keyword(bind) = &document.nodes[2] else {
+            panic!("expected a keyword");
+        };
+        assert!(bind.raw_value.ends_with("bgcolor:#ff0000"));
+    }
+
+    #[test]
+    fn test_errors_do_not_stop_parsing() {
+        let (document, errors) =
+            parse("input {\n  kb_layout = us\nnonsense\n}\n}\nbind = SUPER, Q, killactive\n");
+        let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [3, 5]);
+        assert_eq!(document.nodes.len(), 2);
+    }
+
+    /// Parse every `.conf` file in `testdata/hyprlang` and compare the
+    /// outline with the `.ast` file next to it. Run with
+    /// `UPDATE_GOLDEN=1` to rewrite the expected files.
+    #[test]
+    fn test_golden_corpus() {
+        for path in testutil::testdata_files("hyprlang", "conf") {
+            let (document, errors) = parse(&std::fs::read_to_string(&path).unwrap());
+            let mut actual = document.to_string();
+            for e in errors {
+                actual.push_str(&format!("error {}:{}: {}\n", e.line, e.column, e.message));
+            }
+
+            testutil::assert_golden(&path.with_extension("ast"), &actual);
+        }
+    }
+}
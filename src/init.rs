@@ -0,0 +1,405 @@
+use crate::parser::Keybinding;
+use crate::themes::PRESETS;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Categories written by `init`: id, name, description and the dispatchers
+/// that belong to them. `exec` binds are sorted by program instead.
+const CATEGORIES: &[(&str, &str, &str, &[&str])] = &[
+    (
+        "applications",
+        "Applications",
+        "Launch applications and tools",
+        &[],
+    ),
+    (
+        "windows",
+        "Window Management",
+        "Control windows and their layout",
+        &[
+            "killactive",
+            "forcekillactive",
+            "closewindow",
+            "fullscreen",
+            "fullscreenstate",
+            "fakefullscreen",
+            "togglefloating",
+            "setfloating",
+            "settiled",
+            "pseudo",
+            "pin",
+            "togglesplit",
+            "swapsplit",
+            "togglegroup",
+            "changegroupactive",
+            "moveoutofgroup",
+            "lockgroups",
+            "centerwindow",
+            "layoutmsg",
+        ],
+    ),
+    (
+        "focus",
+        "Window Focus & Movement",
+        "Move focus and swap windows",
+        &[
+            "movefocus",
+            "movewindow",
+            "swapwindow",
+            "cyclenext",
+            "focuswindow",
+            "focusmonitor",
+            "focusurgentorlast",
+            "focuscurrentorlast",
+            "bringactivetotop",
+            "alterzorder",
+        ],
+    ),
+    (
+        "resize",
+        "Window Resizing",
+        "Resize windows",
+        &[
+            "resizeactive",
+            "resizewindow",
+            "resizewindowpixel",
+            "moveactive",
+        ],
+    ),
+    (
+        "workspaces",
+        "Workspace Management",
+        "Switch and manage workspaces",
+        &[
+            "workspace",
+            "movetoworkspace",
+            "movetoworkspacesilent",
+            "togglespecialworkspace",
+            "movecurrentworkspacetomonitor",
+            "moveworkspacetomonitor",
+            "swapactiveworkspaces",
+            "renameworkspace",
+        ],
+    ),
+    (
+        "system",
+        "System Actions",
+        "System controls and utilities",
+        &[
+            "exit",
+            "forcerendererreload",
+            "dpms",
+            "submap",
+            "pass",
+            "sendshortcut",
+            "global",
+        ],
+    ),
+    (
+        "media",
+        "Media Controls",
+        "Audio, brightness and media controls",
+        &[],
+    ),
+];
+
+/// Programs that put an `exec` bind under "system" or "media" rather than
+/// "applications".
+const SYSTEM_PROGRAMS: &[&str] = &[
+    "grim",
+    "slurp",
+    "hyprshot",
+    "grimblast",
+    "hyprlock",
+    "swaylock",
+    "hypridle",
+    "wlogout",
+    "hyprctl",
+    "hyprpicker",
+    "systemctl",
+    "loginctl",
+    "notify-send",
+    "makoctl",
+    "swaync-client",
+];
+const MEDIA_PROGRAMS: &[&str] = &[
+    "wpctl",
+    "pactl",
+    "pamixer",
+    "amixer",
+    "playerctl",
+    "brightnessctl",
+    "light",
+    "swayosd-client",
+];
+
+/// Launchers that run another program, which is the interesting one.
+const WRAPPERS: &[&str] = &["uwsm", "app2unit", "env", "sh", "bash", "setsid", "nohup"];
+
+/// The program an `exec` bind starts, e.g. `firefox` for
+/// `exec, uwsm app -- /usr/bin/firefox --new-window`.
+fn exec_program(params: &str) -> Option<String> {
+    params
+        .split_whitespace()
+        .filter(|word| !word.starts_with('-') && !word.contains('=') && *word != "app")
+        .map(|word| word.rsplit('/').next().unwrap_or(word))
+        .find(|word| !WRAPPERS.contains(word))
+        .map(|word| word.trim_matches(|c| c == '"' || c == '\'').to_string())
+        .filter(|word| !word.is_empty() && !word.starts_with('$'))
+}
+
+/// Quote a string for YAML.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A commented `showkey.yaml` listing `files` (relative to `hypr_dir` where
+/// possible) with categories built from the dispatchers and programs the
+/// `keybindings` use.
+pub fn generate(
+    keybindings: &[Keybinding],
+    files: &[PathBuf],
+    hypr_dir: &Path,
+    theme: &str,
+) -> String {
+    let mut keywords: Vec<BTreeSet<String>> = vec![BTreeSet::new(); CATEGORIES.len()];
+    let index = |id: &str| CATEGORIES.iter().position(|c| c.0 == id).unwrap();
+
+    for kb in keybindings.iter().filter(|kb| !kb.inactive) {
+        let dispatcher = kb.dispatcher().to_lowercase();
+        if dispatcher == "exec" || dispatcher == "execr" {
+            let params = kb.action.split_once(',').map_or("", |(_, p)| p.trim());
+            let Some(program) = exec_program(params) else {
+                continue;
+            };
+            let category = if SYSTEM_PROGRAMS.contains(&program.as_str()) {
+                "system"
+            } else if MEDIA_PROGRAMS.contains(&program.as_str()) {
+                "media"
+            } else {
+                "applications"
+            };
+            keywords[index(category)].insert(program);
+        } else if let Some(i) = CATEGORIES.iter().position(|c| c.3.contains(&&*dispatcher)) {
+            keywords[i].insert(dispatcher);
+        }
+    }
+
+    let mut out = String::from(
+        "# hypr-showkey configuration, generated by `hypr-showkey init`\n\
+         # Every section is optional; see the README for all settings.\n\
+         \n\
+         hyprland_configs:\n  \
+         # Files to read, in order. Relative paths are relative to ~/.config/hypr/.\n  \
+         # `source = ...` lines are followed, so the first file alone would do;\n  \
+         # the others are listed so you can drop the ones you don't want.\n  \
+         files:\n",
+    );
+    for file in files {
+        let display = file.strip_prefix(hypr_dir).unwrap_or(file);
+        let count = keybindings
+            .iter()
+            .filter(|kb| kb.location.file == *file)
+            .count();
+        out.push_str(&format!(
+            "    - {}  # {} keybinding{}\n",
+            quote(&display.to_string_lossy()),
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+    }
+
+    out.push_str(
+        "\n\
+         # Keybindings whose dispatcher, parameters or description contain one of\n\
+         # the keywords go into the category. These lists were built from the\n\
+         # dispatchers and programs found in your binds.\n\
+         categories:\n",
+    );
+    for ((id, name, description, _), keywords) in CATEGORIES.iter().zip(&keywords) {
+        if keywords.is_empty() {
+            continue;
+        }
+        let keywords: Vec<String> = keywords.iter().map(|k| quote(k)).collect();
+        out.push_str(&format!(
+            "  {}:\n    name: {}\n    description: {}\n    keywords: [{}]\n\n",
+            id,
+            quote(name),
+            quote(description),
+            keywords.join(", ")
+        ));
+    }
+
+    out.push_str(&format!(
+        "ui:\n  \
+         # Presets: catppuccin_mocha, catppuccin_latte, catppuccin_macchiato,\n  \
+         # catppuccin_frappe\n  \
+         theme: {}\n",
+        quote(theme)
+    ));
+    out
+}
+
+/// Refuse to replace an existing file unless `force` is set. Checked
+/// before the Hyprland config is read, so the error comes first.
+pub fn check_target(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        bail!(
+            "{} already exists; use --force to overwrite it",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Write the generated configuration, creating its directory.
+pub fn write(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// The preset name for `--theme`, or an error listing the presets.
+pub fn theme_name(name: &str) -> Result<String> {
+    let name = name.to_lowercase();
+    PRESETS
+        .iter()
+        .find(|preset| **preset == name || preset.strip_prefix("catppuccin_") == Some(&name))
+        .map(|preset| preset.to_string())
+        .with_context(|| {
+            format!(
+                "Unknown theme `{}`; choose one of {}",
+                name,
+                PRESETS.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::diagnostics::Location;
+    use crate::parser::HyprlandParser;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn test_generated_config_loads() {
+        let hypr_dir = PathBuf::from("/home/me/.config/hypr");
+        let binds = hypr_dir.join("conf/binds.conf");
+        let bind = |action: &str| Keybinding {
+            key: "SUPER + Q".to_string(),
+            action: action.to_string(),
+            location: Location {
+                file: binds.clone(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let keybindings = [
+            bind("exec, uwsm app -- /usr/bin/firefox --new-window"),
+            bind("exec, playerctl next"),
+            bind("killactive"),
+            bind("movetoworkspace, 2"),
+        ];
+        let files = [hypr_dir.join("hyprland.conf"), binds];
+
+        let content = generate(&keybindings, &files, &hypr_dir, "catppuccin_latte");
+        let config: Config = serde_yaml::from_str(&content).unwrap();
+        let files: Vec<&str> = config
+            .hyprland_configs
+            .files
+            .iter()
+            .map(|f| f.path())
+            .collect();
+        assert_eq!(files, ["hyprland.conf", "conf/binds.conf"]);
+        assert_eq!(config.categories["applications"].keywords, ["firefox"]);
+        assert_eq!(config.categories["media"].keywords, ["playerctl"]);
+        assert_eq!(
+            config.categories["workspaces"].keywords,
+            ["movetoworkspace"]
+        );
+        assert!(!config.categories.contains_key("resize"));
+        assert_eq!(config.ui.theme.name, "catppuccin_latte");
+        assert_eq!(theme_name("Frappe").unwrap(), "catppuccin_frappe");
+    }
+
+    #[test]
+    fn test_exec_program_skips_wrappers_and_variables() {
+        assert_eq!(exec_program("kitty").as_deref(), Some("kitty"));
+        assert_eq!(
+            exec_program("env GDK_SCALE=2 setsid -f /opt/bin/\"obsidian\"").as_deref(),
+            Some("obsidian")
+        );
+        assert_eq!(
+            exec_program("sh -c 'grim -g \"$(slurp)\"'").as_deref(),
+            Some("grim")
+        );
+        assert_eq!(exec_program("$terminal"), None);
+        assert_eq!(exec_program("uwsm app --"), None);
+    }
+
+    #[test]
+    fn test_init_round_trips_the_discovered_files() {
+        let dir = TempDir::new("init");
+        let main = dir.write(
+            "hypr/hyprland.conf",
+            "$terminal = kitty\n\
+             source = conf/*.conf\n\
+             bind = SUPER, Return, exec, $terminal\n\
+             bind = SUPER, L, exec, hyprlock\n",
+        );
+        dir.write(
+            "hypr/conf/binds.conf",
+            "bind = SUPER, F, fullscreen\n\
+             bind = SUPER, R, resizeactive, 10 0\n\
+             # hyprlang if NOPE\n\
+             bind = SUPER, X, togglesplit\n\
+             # hyprlang endif\n",
+        );
+        let elsewhere = dir.write("dots/extra.conf", "");
+
+        let config: Config =
+            serde_yaml::from_str(&format!("hyprland_configs:\n  files: [{:?}]\n", main)).unwrap();
+        let parsed = HyprlandParser::new(&config).parse().unwrap();
+        let mut files = parsed.files.clone();
+        files.push(elsewhere.clone());
+
+        let content = generate(
+            &parsed.keybindings,
+            &files,
+            &dir.join("hypr"),
+            "catppuccin_mocha",
+        );
+        assert!(content.contains("- \"hyprland.conf\"  # 2 keybindings\n"));
+        assert!(content.contains("- \"conf/binds.conf\"  # 3 keybindings\n"));
+        assert!(content.contains(&format!("- {:?}  # 0 keybindings\n", elsewhere)));
+
+        // With base_dir pointing at the same directory, the listed files
+        // are the ones that were discovered
+        let output = dir.join("showkey/showkey.yaml");
+        check_target(&output, false).unwrap();
+        let content = content.replace(
+            "hyprland_configs:\n",
+            &format!("hyprland_configs:\n  base_dir: {:?}\n", dir.join("hypr")),
+        );
+        write(&output, &content).unwrap();
+        let config = Config::load_from(&output, crate::appearance::known).unwrap();
+        assert_eq!(
+            config.resolve_hyprland_paths(&mut Vec::new()).unwrap(),
+            files
+        );
+        assert_eq!(config.categories["windows"].keywords, ["fullscreen"]);
+        assert_eq!(config.categories["resize"].keywords, ["resizeactive"]);
+        assert_eq!(config.categories["applications"].keywords, ["kitty"]);
+        assert_eq!(config.categories["system"].keywords, ["hyprlock"]);
+
+        let error = check_target(&output, false).unwrap_err().to_string();
+        assert!(error.contains("use --force"), "{}", error);
+        check_target(&output, true).unwrap();
+        assert!(theme_name("solarized").is_err());
+    }
+}
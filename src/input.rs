@@ -0,0 +1,223 @@
+use unicode_width::UnicodeWidthStr;
+
+/// A single-line text editor backing the search bar.
+///
+/// The cursor is stored as a character index (not a byte offset) so that
+/// multi-byte input can be edited safely.
+#[derive(Debug, Clone, Default)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn value(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Terminal cells taken by the text before the cursor, i.e. the column
+    /// to draw the cursor at. Wide characters such as CJK take two.
+    pub fn cursor_width(&self) -> usize {
+        self.text[..self.byte_index(self.cursor)].width()
+    }
+
+    /// Replace the whole line and move the cursor to the end.
+    pub fn set_value(&mut self, value: &str) {
+        self.text = value.to_string();
+        self.cursor = self.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let pos = self.byte_index(self.cursor);
+        self.text.insert(pos, c);
+        self.cursor += 1;
+    }
+
+    /// Insert a string at the cursor. Newlines and other control characters
+    /// are dropped since the search bar is a single line.
+    pub fn insert_str(&mut self, s: &str) {
+        for c in s.chars().filter(|c| !c.is_control()) {
+            self.insert_char(c);
+        }
+    }
+
+    /// Delete the character before the cursor.
+    pub fn backspace(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.cursor -= 1;
+        let pos = self.byte_index(self.cursor);
+        self.text.remove(pos);
+        true
+    }
+
+    /// Delete the character under the cursor.
+    pub fn delete(&mut self) -> bool {
+        if self.cursor >= self.len() {
+            return false;
+        }
+        let pos = self.byte_index(self.cursor);
+        self.text.remove(pos);
+        true
+    }
+
+    /// Delete the word before the cursor, along with any whitespace between
+    /// it and the cursor.
+    pub fn delete_word_back(&mut self) -> bool {
+        let start = self.word_start_before(self.cursor);
+        if start == self.cursor {
+            return false;
+        }
+        let (from, to) = (self.byte_index(start), self.byte_index(self.cursor));
+        self.text.replace_range(from..to, "");
+        self.cursor = start;
+        true
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.len();
+    }
+
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.word_start_before(self.cursor);
+    }
+
+    pub fn move_word_right(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut pos = self.cursor;
+        while pos < chars.len() && chars[pos].is_whitespace() {
+            pos += 1;
+        }
+        while pos < chars.len() && !chars[pos].is_whitespace() {
+            pos += 1;
+        }
+        self.cursor = pos;
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map(|(i, _)| i)
+            .unwrap_or(self.text.len())
+    }
+
+    fn word_start_before(&self, from: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut pos = from;
+        while pos > 0 && chars[pos - 1].is_whitespace() {
+            pos -= 1;
+        }
+        while pos > 0 && !chars[pos - 1].is_whitespace() {
+            pos -= 1;
+        }
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str) -> LineEditor {
+        let mut editor = LineEditor::new();
+        editor.set_value(text);
+        editor
+    }
+
+    #[test]
+    fn test_insert_at_cursor() {
+        let mut ed = editor("kity");
+        ed.move_left();
+        ed.move_left();
+        ed.insert_char('t');
+        assert_eq!(ed.value(), "kitty");
+        assert_eq!(ed.cursor, 3);
+    }
+
+    #[test]
+    fn test_delete_and_backspace() {
+        let mut ed = editor("ab");
+        ed.move_home();
+        assert!(!ed.backspace());
+        assert!(ed.delete());
+        assert_eq!(ed.value(), "b");
+        ed.move_end();
+        assert!(!ed.delete());
+        assert!(ed.backspace());
+        assert!(ed.is_empty());
+    }
+
+    #[test]
+    fn test_delete_word_back() {
+        let mut ed = editor("exec kitty  ");
+        assert!(ed.delete_word_back());
+        assert_eq!(ed.value(), "exec ");
+        assert!(ed.delete_word_back());
+        assert_eq!(ed.value(), "");
+        assert!(!ed.delete_word_back());
+    }
+
+    #[test]
+    fn test_word_movement() {
+        let mut ed = editor("move focus left");
+        ed.move_word_left();
+        assert_eq!(ed.cursor, 11);
+        ed.move_word_left();
+        ed.move_word_left();
+        assert_eq!(ed.cursor, 0);
+        ed.move_word_right();
+        assert_eq!(ed.cursor, 4);
+    }
+
+    #[test]
+    fn test_multibyte_editing() {
+        let mut ed = editor("a→b");
+        ed.move_left();
+        assert!(ed.backspace());
+        assert_eq!(ed.value(), "ab");
+
+        let mut ed = editor("日本x");
+        assert_eq!((ed.cursor, ed.cursor_width()), (3, 5));
+        ed.move_left();
+        ed.move_left();
+        assert_eq!((ed.cursor, ed.cursor_width()), (1, 2));
+    }
+
+    #[test]
+    fn test_paste_strips_newlines() {
+        let mut ed = LineEditor::new();
+        ed.insert_str("work\nspace\t");
+        assert_eq!(ed.value(), "workspace");
+    }
+}
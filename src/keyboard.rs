@@ -0,0 +1,529 @@
+use anyhow::{Context, Result};
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::{
+    keymap::{Action, Keymap},
+    parser::{normalize_key, Keybinding},
+    paths,
+    theme::Theme,
+};
+
+/// Terminal cells per key unit (a standard letter key).
+const CELLS_PER_UNIT: f32 = 7.0;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyboardLayout {
+    pub name: String,
+    pub rows: Vec<Vec<KeyCap>>,
+}
+
+/// One key on the on-screen keyboard. In layout files a key can be written
+/// as just its keysym (`"Q"`) or as a map with a label and width.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "KeyCapSpec")]
+pub struct KeyCap {
+    /// Keysym as written in Hyprland binds, e.g. `Return` or `grave`.
+    pub key: String,
+    pub label: String,
+    /// Width in key units; 1.0 is a letter key.
+    pub width: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyCapSpec {
+    Key(String),
+    Full {
+        key: String,
+        label: Option<String>,
+        width: Option<f32>,
+    },
+}
+
+impl From<KeyCapSpec> for KeyCap {
+    fn from(spec: KeyCapSpec) -> Self {
+        match spec {
+            KeyCapSpec::Key(key) => KeyCap::new(&key, 1.0),
+            KeyCapSpec::Full { key, label, width } => {
+                let mut cap = KeyCap::new(&key, width.unwrap_or(1.0));
+                if let Some(label) = label {
+                    cap.label = label;
+                }
+                cap
+            }
+        }
+    }
+}
+
+impl KeyCap {
+    fn new(key: &str, width: f32) -> Self {
+        Self {
+            key: key.to_string(),
+            label: default_label(key),
+            width,
+        }
+    }
+}
+
+/// A short label for keysyms whose names are too long for a key cap.
+fn default_label(key: &str) -> String {
+    match key {
+        "grave" => "`",
+        "minus" => "-",
+        "equal" => "=",
+        "bracketleft" => "[",
+        "bracketright" => "]",
+        "backslash" => "\\",
+        "semicolon" => ";",
+        "apostrophe" => "'",
+        "comma" => ",",
+        "period" => ".",
+        "slash" => "/",
+        "numbersign" => "#",
+        "less" => "<",
+        "Escape" => "Esc",
+        "BackSpace" => "Bksp",
+        "Caps_Lock" => "Caps",
+        "Return" => "Enter",
+        "Shift_L" | "Shift_R" => "Shift",
+        "Control_L" | "Control_R" => "Ctrl",
+        "Super_L" | "Super_R" => "Super",
+        "Alt_L" | "Alt_R" => "Alt",
+        "space" => "Space",
+        "Print" => "Prt",
+        "Insert" => "Ins",
+        "Delete" => "Del",
+        "Prior" => "PgUp",
+        "Next" => "PgDn",
+        "Left" => "←",
+        "Right" => "→",
+        "Up" => "↑",
+        "Down" => "↓",
+        _ => key,
+    }
+    .to_string()
+}
+
+fn row(keys: &[(&str, f32)]) -> Vec<KeyCap> {
+    keys.iter()
+        .map(|(key, width)| KeyCap::new(key, *width))
+        .collect()
+}
+
+fn letters(keys: &str) -> Vec<(&str, f32)> {
+    keys.split_whitespace().map(|key| (key, 1.0)).collect()
+}
+
+impl KeyboardLayout {
+    /// Resolve `ui.keyboard_layout`: a built-in name (`ansi`, `iso`) or a
+    /// path to a YAML layout file, with `~` and variables expanded and
+    /// relative to `~/.config/hypr-showkey/`.
+    pub fn load(spec: &str) -> Result<Self> {
+        match spec.to_lowercase().as_str() {
+            "ansi" => return Ok(Self::ansi()),
+            "iso" => return Ok(Self::iso()),
+            _ => {}
+        }
+
+        let mut path = PathBuf::from(paths::expand(spec, |name| std::env::var(name).ok())?);
+        if path.is_relative() {
+            if let Some(config_dir) = dirs::config_dir() {
+                path = config_dir.join("hypr-showkey").join(path);
+            }
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read keyboard layout: {:?}", path))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse keyboard layout: {:?}", path))
+    }
+
+    pub fn ansi() -> Self {
+        Self::with_alpha_rows(
+            "ansi",
+            [
+                [
+                    &[("Tab", 1.5)],
+                    &letters("Q W E R T Y U I O P bracketleft bracketright")[..],
+                    &[("backslash", 1.5)],
+                ]
+                .concat(),
+                [
+                    &[("Caps_Lock", 1.75)],
+                    &letters("A S D F G H J K L semicolon apostrophe")[..],
+                    &[("Return", 2.25)],
+                ]
+                .concat(),
+                [
+                    &[("Shift_L", 2.25)],
+                    &letters("Z X C V B N M comma period slash")[..],
+                    &[("Shift_R", 2.75)],
+                ]
+                .concat(),
+            ],
+        )
+    }
+
+    pub fn iso() -> Self {
+        Self::with_alpha_rows(
+            "iso",
+            [
+                [
+                    &[("Tab", 1.5)],
+                    &letters("Q W E R T Y U I O P bracketleft bracketright")[..],
+                    &[("Return", 1.5)],
+                ]
+                .concat(),
+                [
+                    &[("Caps_Lock", 1.75)],
+                    &letters("A S D F G H J K L semicolon apostrophe numbersign")[..],
+                    &[("Return", 1.25)],
+                ]
+                .concat(),
+                [
+                    &[("Shift_L", 1.25)],
+                    &letters("less Z X C V B N M comma period slash")[..],
+                    &[("Shift_R", 2.75)],
+                ]
+                .concat(),
+            ],
+        )
+    }
+
+    /// Layouts only differ in the three letter rows; the function row,
+    /// number row, bottom row and navigation keys are shared.
+    fn with_alpha_rows(name: &str, alpha: [Vec<(&str, f32)>; 3]) -> Self {
+        let function_row = [
+            &[("Escape", 1.0)],
+            &letters("F1 F2 F3 F4 F5 F6 F7 F8 F9 F10 F11 F12")[..],
+        ]
+        .concat();
+        let number_row = [
+            &letters("grave 1 2 3 4 5 6 7 8 9 0 minus equal")[..],
+            &[("BackSpace", 2.0)],
+        ]
+        .concat();
+        let bottom_row = [
+            ("Control_L", 1.25),
+            ("Super_L", 1.25),
+            ("Alt_L", 1.25),
+            ("space", 6.25),
+            ("Alt_R", 1.25),
+            ("Super_R", 1.25),
+            ("Menu", 1.25),
+            ("Control_R", 1.25),
+        ];
+        let navigation_row = letters("Print Insert Home Prior Delete End Next Left Up Down Right");
+
+        let [top, middle, lower] = alpha;
+        Self {
+            name: name.to_string(),
+            rows: vec![
+                row(&function_row),
+                row(&number_row),
+                row(&top),
+                row(&middle),
+                row(&lower),
+                row(&bottom_row),
+                row(&navigation_row),
+            ],
+        }
+    }
+}
+
+/// State of the keyboard heatmap view.
+pub struct KeyboardView {
+    pub layout: KeyboardLayout,
+    /// Normalized modifier names that are toggled on.
+    pub modifiers: Vec<String>,
+    /// (row, column) of the highlighted key.
+    pub cursor: (usize, usize),
+    /// Screen area of every key from the last draw, for mouse clicks.
+    pub key_areas: Vec<((usize, usize), Rect)>,
+}
+
+impl KeyboardView {
+    pub fn new(layout: KeyboardLayout) -> Self {
+        // Start on Q in the built-in layouts, where most binds live
+        let cursor = if layout.rows.len() > 2 && layout.rows[2].len() > 1 {
+            (2, 1)
+        } else {
+            (0, 0)
+        };
+        Self {
+            layout,
+            modifiers: vec!["SUPER".to_string()],
+            cursor,
+            key_areas: Vec::new(),
+        }
+    }
+
+    pub fn toggle_modifier(&mut self, modifier: &str) {
+        if let Some(pos) = self.modifiers.iter().position(|m| m == modifier) {
+            self.modifiers.remove(pos);
+        } else {
+            self.modifiers.push(modifier.to_string());
+            self.modifiers.sort();
+        }
+    }
+
+    pub fn selected_key(&self) -> Option<&KeyCap> {
+        let (row, col) = self.cursor;
+        self.layout.rows.get(row)?.get(col)
+    }
+
+    /// Bindings that use `key` with exactly the toggled modifiers. Only
+    /// global bindings count; submaps have their own key space.
+    pub fn bindings_for<'a>(
+        &self,
+        key: &KeyCap,
+        keybindings: &'a [Keybinding],
+    ) -> Vec<&'a Keybinding> {
+        let key = normalize_key(&key.key);
+        keybindings
+            .iter()
+            .filter(|kb| !kb.inactive && kb.submap.is_none())
+            .filter(|kb| kb.modifiers() == self.modifiers)
+            .filter(|kb| kb.key_name() == key)
+            .collect()
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor.1 = self.cursor.1.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        let len = self.layout.rows.get(self.cursor.0).map_or(0, Vec::len);
+        self.cursor.1 = (self.cursor.1 + 1).min(len.saturating_sub(1));
+    }
+
+    pub fn move_up(&mut self) {
+        if self.cursor.0 > 0 {
+            self.move_to_row(self.cursor.0 - 1);
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.cursor.0 + 1 < self.layout.rows.len() {
+            self.move_to_row(self.cursor.0 + 1);
+        }
+    }
+
+    /// Move vertically to the key whose centre is closest to the current one.
+    fn move_to_row(&mut self, row: usize) {
+        let centre = self.key_centre(self.cursor.0, self.cursor.1);
+        let col = (0..self.layout.rows[row].len())
+            .min_by(|&a, &b| {
+                let da = (self.key_centre(row, a) - centre).abs();
+                let db = (self.key_centre(row, b) - centre).abs();
+                da.total_cmp(&db)
+            })
+            .unwrap_or(0);
+        self.cursor = (row, col);
+    }
+
+    fn key_centre(&self, row: usize, col: usize) -> f32 {
+        let keys = &self.layout.rows[row];
+        let start: f32 = keys.iter().take(col).map(|k| k.width).sum();
+        start + keys.get(col).map_or(0.0, |k| k.width / 2.0)
+    }
+
+    pub fn render(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        keybindings: &[Keybinding],
+        keymap: &Keymap,
+        theme: &Theme,
+    ) {
+        let title = format!(
+            "Keyboard ({}) - {}{}",
+            self.layout.name,
+            if self.modifiers.is_empty() {
+                "no modifiers".to_string()
+            } else {
+                self.modifiers.join(" + ")
+            },
+            toggle_hint(keymap)
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(theme.border());
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        // Scale key widths down if the widest row doesn't fit
+        let widest = self
+            .layout
+            .rows
+            .iter()
+            .map(|keys| keys.iter().map(|k| k.width).sum::<f32>())
+            .fold(0.0, f32::max);
+        let cells_per_unit = if widest > 0.0 {
+            (inner.width as f32 / widest).min(CELLS_PER_UNIT)
+        } else {
+            CELLS_PER_UNIT
+        };
+        let rows = self.layout.rows.len() as u16;
+        let key_height = if inner.height >= rows * 3 { 3 } else { 1 };
+
+        self.key_areas.clear();
+        for (row_idx, keys) in self.layout.rows.iter().enumerate() {
+            let y = inner.y + row_idx as u16 * key_height;
+            if y + key_height > inner.y + inner.height {
+                break;
+            }
+
+            let mut units = 0.0;
+            for (col_idx, key) in keys.iter().enumerate() {
+                let x = inner.x + (units * cells_per_unit).round() as u16;
+                units += key.width;
+                let right = inner.x + (units * cells_per_unit).round() as u16;
+                let width = right.saturating_sub(x).saturating_sub(1).max(1);
+                let key_area = Rect::new(x, y, width, key_height).intersection(inner);
+
+                let count = self.bindings_for(key, keybindings).len();
+                let is_cursor = self.cursor == (row_idx, col_idx);
+                f.render_widget(
+                    key_widget(key, count, is_cursor, key_height, theme),
+                    key_area,
+                );
+                self.key_areas.push(((row_idx, col_idx), key_area));
+            }
+        }
+    }
+}
+
+/// The keys that toggle each modifier, as ` [1 Super 2 Shift ...]`; toggles
+/// with no key are left out.
+fn toggle_hint(keymap: &Keymap) -> String {
+    let toggles: Vec<String> = [
+        (Action::ToggleSuper, "Super"),
+        (Action::ToggleShift, "Shift"),
+        (Action::ToggleCtrl, "Ctrl"),
+        (Action::ToggleAlt, "Alt"),
+    ]
+    .into_iter()
+    .filter_map(|(action, label)| Some(format!("{} {}", keymap.hint(action, false)?, label)))
+    .collect();
+    if toggles.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", toggles.join(" "))
+    }
+}
+
+/// Render one key, coloured by how many bindings use it: free keys are
+/// dim, keys with one binding use the category colour and keys with more
+/// than one (usually conflicts or submap-less duplicates) are highlighted.
+fn key_widget<'a>(
+    key: &'a KeyCap,
+    count: usize,
+    is_cursor: bool,
+    height: u16,
+    theme: &Theme,
+) -> Paragraph<'a> {
+    let mut style = match count {
+        0 => theme.muted(),
+        1 => Style::default().fg(theme.background).bg(theme.category),
+        _ => Style::default().fg(theme.background).bg(theme.matched),
+    };
+    if is_cursor {
+        style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+    }
+
+    let mut paragraph = Paragraph::new(key.label.as_str())
+        .alignment(Alignment::Center)
+        .style(style);
+    if height >= 3 {
+        paragraph = paragraph.block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(theme.border()),
+        );
+    }
+    paragraph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(key: &str) -> Keybinding {
+        Keybinding {
+            key: key.to_string(),
+            action: "exec, kitty".to_string(),
+            description: String::new(),
+            category: "Other".to_string(),
+            raw_command: String::new(),
+            submap: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bindings_match_exact_modifier_set() {
+        let keybindings = vec![
+            binding("Super + Q"),
+            binding("SUPER + SHIFT + q"),
+            binding("Super + Return"),
+        ];
+        let mut view = KeyboardView::new(KeyboardLayout::ansi());
+        let q = KeyCap::new("Q", 1.0);
+        let enter = KeyCap::new("Return", 2.25);
+
+        assert_eq!(view.bindings_for(&q, &keybindings).len(), 1);
+        assert_eq!(view.bindings_for(&enter, &keybindings).len(), 1);
+        view.toggle_modifier("SHIFT");
+        assert_eq!(
+            view.bindings_for(&q, &keybindings)[0].key,
+            "SUPER + SHIFT + q"
+        );
+        assert!(view.bindings_for(&enter, &keybindings).is_empty());
+    }
+
+    #[test]
+    fn test_vertical_movement_keeps_column() {
+        let mut view = KeyboardView::new(KeyboardLayout::ansi());
+        view.cursor = (2, 1); // Q
+        view.move_down();
+        assert_eq!(view.selected_key().unwrap().key, "A");
+        view.move_down();
+        assert_eq!(view.selected_key().unwrap().key, "Z");
+    }
+
+    #[test]
+    fn test_layout_file_accepts_short_and_full_keys() {
+        let layout: KeyboardLayout = serde_yaml::from_str(
+            "name: tiny\nrows:\n  - [Q, W, { key: Return, label: Ret, width: 2 }]\n",
+        )
+        .unwrap();
+        assert_eq!(layout.rows[0][0].label, "Q");
+        assert_eq!(layout.rows[0][2].label, "Ret");
+        assert_eq!(layout.rows[0][2].width, 2.0);
+
+        let dir = crate::testutil::TempDir::new("layout");
+        dir.write("tiny.yaml", "name: tiny\nrows:\n  - [Q]\n");
+        std::env::set_var("SHOWKEY_TEST_LAYOUTS", dir.path());
+        let layout = KeyboardLayout::load("$SHOWKEY_TEST_LAYOUTS/tiny.yaml").unwrap();
+        assert_eq!(layout.name, "tiny");
+    }
+
+    #[test]
+    fn test_title_names_the_toggle_keys() {
+        assert_eq!(
+            toggle_hint(&Keymap::default()),
+            " [1 Super 2 Shift 3 Ctrl 4 Alt]"
+        );
+        let keymap: Keymap = serde_yaml::from_str("toggle_super: s\ntoggle_alt: []").unwrap();
+        assert_eq!(toggle_hint(&keymap), " [s Super 2 Shift 3 Ctrl]");
+    }
+}
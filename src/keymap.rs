@@ -0,0 +1,763 @@
+use anyhow::{bail, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Something a key can do in the TUI, named in `ui.keymap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    ClearSearch,
+    Search,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Activate,
+    Copy,
+    Execute,
+    Edit,
+    ToggleDetail,
+    Pin,
+    Sort,
+    SortPrevious,
+    Layout,
+    ColumnNext,
+    ColumnPrevious,
+    ColumnWider,
+    ColumnNarrower,
+    NextTab,
+    PreviousTab,
+    Keyboard,
+    FreeKeys,
+    Diagnostics,
+    ToggleInactive,
+    Help,
+    HistoryPrevious,
+    HistoryNext,
+    ToggleSuper,
+    ToggleShift,
+    ToggleCtrl,
+    ToggleAlt,
+    CursorLeft,
+    CursorRight,
+    WordLeft,
+    WordRight,
+    LineStart,
+    LineEnd,
+    DeleteBack,
+    DeleteForward,
+    DeleteWord,
+    ClearLine,
+}
+
+/// Every action with its name, help text and default keys, in the order the
+/// help lists them. The editing actions come last.
+const ACTIONS: [(Action, &str, &str, &[&str]); 47] = [
+    (Action::Up, "up", "Move up", &["k", "Up", "Ctrl-k"]),
+    (Action::Down, "down", "Move down", &["j", "Down", "Ctrl-j"]),
+    (
+        Action::Left,
+        "left",
+        "Previous column or key",
+        &["h", "Left"],
+    ),
+    (
+        Action::Right,
+        "right",
+        "Next column or key",
+        &["l", "Right"],
+    ),
+    (Action::PageUp, "page_up", "Page up", &["PageUp"]),
+    (Action::PageDown, "page_down", "Page down", &["PageDown"]),
+    (Action::Top, "top", "First keybinding", &["g", "Home"]),
+    (Action::Bottom, "bottom", "Last keybinding", &["G", "End"]),
+    (
+        Action::Search,
+        "search",
+        "Enter search (insert) mode",
+        &["/", "i"],
+    ),
+    (
+        Action::Activate,
+        "activate",
+        "Run the Enter action (ui.enter_action)",
+        &["Enter"],
+    ),
+    (Action::Copy, "copy", "Copy the key combination", &["y"]),
+    (
+        Action::Execute,
+        "execute",
+        "Run the binding with hyprctl dispatch",
+        &[],
+    ),
+    (Action::Edit, "edit", "Open the binding in $EDITOR", &["e"]),
+    (
+        Action::ToggleDetail,
+        "toggle_detail",
+        "Show the binding's details",
+        &["d"],
+    ),
+    (
+        Action::Pin,
+        "pin",
+        "Pin/unpin keybinding",
+        &["m", "*", "Ctrl-t"],
+    ),
+    (
+        Action::Sort,
+        "sort",
+        "Next sort order (file, key, modifiers, ...)",
+        &["s", "Ctrl-s"],
+    ),
+    (
+        Action::SortPrevious,
+        "sort_previous",
+        "Previous sort order",
+        &["S"],
+    ),
+    (
+        Action::Layout,
+        "layout",
+        "Switch between the list and table layout",
+        &["t"],
+    ),
+    (
+        Action::ColumnNext,
+        "column_next",
+        "Table: pick the next column to resize",
+        &["]"],
+    ),
+    (
+        Action::ColumnPrevious,
+        "column_previous",
+        "Table: pick the previous column to resize",
+        &["["],
+    ),
+    (
+        Action::ColumnWider,
+        "column_wider",
+        "Table: widen the picked column",
+        &[">"],
+    ),
+    (
+        Action::ColumnNarrower,
+        "column_narrower",
+        "Table: narrow the picked column",
+        &["<"],
+    ),
+    (Action::NextTab, "next_tab", "Next tab", &["Tab"]),
+    (
+        Action::PreviousTab,
+        "previous_tab",
+        "Previous tab",
+        &["Shift-Tab"],
+    ),
+    (
+        Action::Keyboard,
+        "keyboard",
+        "Keyboard heatmap view and back",
+        &["K", "F2"],
+    ),
+    (
+        Action::FreeKeys,
+        "free_keys",
+        "Free keys for the selected modifiers",
+        &["F", "f"],
+    ),
+    (
+        Action::Diagnostics,
+        "diagnostics",
+        "Parse problems (diagnostics)",
+        &["D", "F3"],
+    ),
+    (
+        Action::ToggleInactive,
+        "toggle_inactive",
+        "Show/hide inactive (hyprlang if)",
+        &["I"],
+    ),
+    (
+        Action::HistoryPrevious,
+        "history_previous",
+        "Previous search in history",
+        &["Ctrl-p"],
+    ),
+    (
+        Action::HistoryNext,
+        "history_next",
+        "Next search in history",
+        &["Ctrl-n"],
+    ),
+    (
+        Action::ToggleSuper,
+        "toggle_super",
+        "Keyboard view: toggle Super",
+        &["1"],
+    ),
+    (
+        Action::ToggleShift,
+        "toggle_shift",
+        "Keyboard view: toggle Shift",
+        &["2"],
+    ),
+    (
+        Action::ToggleCtrl,
+        "toggle_ctrl",
+        "Keyboard view: toggle Ctrl",
+        &["3"],
+    ),
+    (
+        Action::ToggleAlt,
+        "toggle_alt",
+        "Keyboard view: toggle Alt",
+        &["4"],
+    ),
+    (Action::Help, "help", "Toggle this help", &["?", "F1"]),
+    (
+        Action::ClearSearch,
+        "clear_search",
+        "Clear search, then leave search mode or quit",
+        &["Esc"],
+    ),
+    (Action::Quit, "quit", "Quit application", &["q"]),
+    (
+        Action::CursorLeft,
+        "cursor_left",
+        "Move the cursor left",
+        &["Left", "Ctrl-b"],
+    ),
+    (
+        Action::CursorRight,
+        "cursor_right",
+        "Move the cursor right",
+        &["Right", "Ctrl-f"],
+    ),
+    (
+        Action::WordLeft,
+        "word_left",
+        "Move to the previous word",
+        &["Ctrl-Left", "Alt-b"],
+    ),
+    (
+        Action::WordRight,
+        "word_right",
+        "Move to the next word",
+        &["Ctrl-Right", "Alt-f"],
+    ),
+    (
+        Action::LineStart,
+        "line_start",
+        "Start of the query",
+        &["Home", "Ctrl-a"],
+    ),
+    (
+        Action::LineEnd,
+        "line_end",
+        "End of the query",
+        &["End", "Ctrl-e"],
+    ),
+    (
+        Action::DeleteBack,
+        "delete_back",
+        "Delete the character before the cursor",
+        &["Backspace", "Ctrl-h"],
+    ),
+    (
+        Action::DeleteForward,
+        "delete_forward",
+        "Delete the character under the cursor",
+        &["Delete"],
+    ),
+    (
+        Action::DeleteWord,
+        "delete_word",
+        "Delete the word before the cursor",
+        &["Ctrl-w", "Alt-Backspace", "Ctrl-Backspace"],
+    ),
+    (
+        Action::ClearLine,
+        "clear_line",
+        "Clear the query",
+        &["Ctrl-u"],
+    ),
+];
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        Self::entry(self).1
+    }
+
+    fn entry(self) -> &'static (Action, &'static str, &'static str, &'static [&'static str]) {
+        ACTIONS.iter().find(|entry| entry.0 == self).unwrap()
+    }
+
+    /// Whether this edits the query in the search box. Editing actions have
+    /// keys of their own, which take precedence while searching.
+    pub fn is_editing(self) -> bool {
+        matches!(
+            self,
+            Action::CursorLeft
+                | Action::CursorRight
+                | Action::WordLeft
+                | Action::WordRight
+                | Action::LineStart
+                | Action::LineEnd
+                | Action::DeleteBack
+                | Action::DeleteForward
+                | Action::DeleteWord
+                | Action::ClearLine
+        )
+    }
+
+    /// The modifier a keyboard view toggle adds or removes, as named in
+    /// the keyboard view.
+    pub fn modifier(self) -> Option<&'static str> {
+        match self {
+            Action::ToggleSuper => Some("SUPER"),
+            Action::ToggleShift => Some("SHIFT"),
+            Action::ToggleCtrl => Some("CTRL"),
+            Action::ToggleAlt => Some("ALT"),
+            _ => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        ACTIONS
+            .iter()
+            .find(|entry| entry.1 == name)
+            .map(|entry| entry.0)
+    }
+}
+
+/// A key with modifiers, such as `Ctrl-j` or `Shift-Tab`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+/// Named keys, as written in `ui.keymap` (case doesn't matter) and shown
+/// in the help.
+const KEY_NAMES: [(&str, KeyCode); 16] = [
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Tab", KeyCode::Tab),
+    ("Space", KeyCode::Char(' ')),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Return", KeyCode::Enter),
+];
+
+impl KeyChord {
+    /// Parse `q`, `G`, `Ctrl-j`, `Alt+b`, `Shift-Tab`, `F1`, `PageDown`...
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec.trim();
+        // Modifier prefixes; what's left is the key, which may itself be `-`
+        'prefixes: loop {
+            for (names, modifier) in [
+                (&["ctrl", "control", "c"][..], KeyModifiers::CONTROL),
+                (&["alt", "meta", "m", "a"][..], KeyModifiers::ALT),
+                (&["shift", "s"][..], KeyModifiers::SHIFT),
+            ] {
+                for name in names {
+                    for separator in ['-', '+'] {
+                        let prefix = format!("{}{}", name, separator);
+                        let matches = rest.len() > prefix.len()
+                            && rest[..prefix.len()].eq_ignore_ascii_case(&prefix);
+                        if matches {
+                            modifiers |= modifier;
+                            rest = &rest[prefix.len()..];
+                            continue 'prefixes;
+                        }
+                    }
+                }
+            }
+            break;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (None, _) => bail!("no key in `{}`", spec),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                if let Some(code) = KEY_NAMES
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(rest))
+                    .map(|(_, code)| *code)
+                {
+                    code
+                } else if let Some(n) = rest
+                    .strip_prefix(['F', 'f'])
+                    .and_then(|n| n.parse::<u8>().ok())
+                    .filter(|n| (1..=24).contains(n))
+                {
+                    KeyCode::F(n)
+                } else {
+                    bail!("unknown key `{}` in `{}`", rest, spec);
+                }
+            }
+        };
+
+        Ok(Self::normalize(code, modifiers))
+    }
+
+    /// The chord for a key press.
+    pub fn from_event(event: &KeyEvent) -> Self {
+        Self::normalize(event.code, event.modifiers)
+    }
+
+    /// Ctrl-c, which always quits and can't be rebound.
+    pub fn is_interrupt(&self) -> bool {
+        self.code == KeyCode::Char('c') && self.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    /// Shift is part of the character for letters and symbols (`K`, `?`),
+    /// and Shift-Tab arrives as BackTab.
+    fn normalize(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => Self {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => Self {
+                code: KeyCode::Char(c.to_ascii_lowercase()),
+                modifiers,
+            },
+            KeyCode::BackTab | KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => Self {
+                code: KeyCode::Tab,
+                modifiers,
+            },
+            KeyCode::BackTab => Self {
+                code: KeyCode::Tab,
+                modifiers: modifiers | KeyModifiers::SHIFT,
+            },
+            code => Self { code, modifiers },
+        }
+    }
+
+    /// Whether this types a character, which goes into the query while
+    /// searching instead of running an action.
+    pub fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl-"),
+            (KeyModifiers::ALT, "Alt-"),
+            (KeyModifiers::SHIFT, "Shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => match KEY_NAMES.iter().find(|(_, named)| *named == code) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+/// `ui.keymap`: the keys for each action. Actions not mentioned keep their
+/// default keys, minus any that the config gives to another action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(KeyChord, Action)>,
+}
+
+/// One key or a list of keys for an action.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keymap {
+    /// Layer the configured keys over the defaults. Unknown actions, keys
+    /// that don't parse, a key given to two actions, `Ctrl-c` and a `quit`
+    /// with no keys left are errors.
+    pub fn with_overrides(overrides: &[(String, Vec<String>)]) -> Result<Self> {
+        // Keys are unique within the editing actions and within the others
+        let mut configured: HashMap<(KeyChord, bool), Action> = HashMap::new();
+        let mut bindings = Vec::new();
+        for (name, keys) in overrides {
+            let action = Action::from_name(name).with_context(|| {
+                format!(
+                    "unknown action `{}`; expected one of {}",
+                    name,
+                    ACTIONS
+                        .iter()
+                        .map(|entry| entry.1)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+            for key in keys {
+                let chord =
+                    KeyChord::parse(key).with_context(|| format!("{}: invalid key", name))?;
+                if chord.is_interrupt() {
+                    bail!("{}: `{}` always quits and can't be rebound", name, chord);
+                }
+                if let Some(other) = configured.insert((chord, action.is_editing()), action) {
+                    if other != action {
+                        bail!(
+                            "`{}` is bound to both `{}` and `{}`",
+                            chord,
+                            other.name(),
+                            action.name()
+                        );
+                    }
+                }
+                bindings.push((chord, action));
+            }
+        }
+
+        for (action, _, _, defaults) in ACTIONS {
+            if overrides.iter().any(|(name, _)| name == action.name()) {
+                continue;
+            }
+            for key in defaults {
+                let chord = KeyChord::parse(key).expect("default keys parse");
+                if !configured.contains_key(&(chord, action.is_editing())) {
+                    bindings.push((chord, action));
+                }
+            }
+        }
+
+        if !bindings.iter().any(|(_, action)| *action == Action::Quit) {
+            bail!("`quit` has no keys left; give it at least one");
+        }
+
+        Ok(Self { bindings })
+    }
+
+    /// The action for a key press, if any, outside the search box.
+    pub fn action(&self, chord: &KeyChord) -> Option<Action> {
+        self.lookup(chord, false)
+    }
+
+    /// The editing action for a key press in the search box, if any.
+    pub fn editing_action(&self, chord: &KeyChord) -> Option<Action> {
+        self.lookup(chord, true)
+    }
+
+    fn lookup(&self, chord: &KeyChord, editing: bool) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(bound, action)| bound == chord && action.is_editing() == editing)
+            .map(|(_, action)| *action)
+    }
+
+    /// The keys that run `action`, in the order they were configured.
+    pub fn keys(&self, action: Action) -> Vec<KeyChord> {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(chord, _)| *chord)
+            .collect()
+    }
+
+    /// The first key for `action` to mention in a hint; `searching` skips
+    /// keys that would be typed into the query.
+    pub fn hint(&self, action: Action, searching: bool) -> Option<String> {
+        self.keys(action)
+            .into_iter()
+            .find(|chord| !searching || !chord.is_text())
+            .map(|chord| chord.to_string())
+    }
+
+    /// Help lines for the editing actions or for the others: their keys,
+    /// then what they do.
+    pub fn help_lines(&self, editing: bool) -> Vec<String> {
+        let rows: Vec<(String, &str)> = ACTIONS
+            .iter()
+            .filter(|(action, _, _, _)| action.is_editing() == editing)
+            .map(|(action, _, description, _)| {
+                let keys: Vec<String> = self.keys(*action).iter().map(|c| c.to_string()).collect();
+                let keys = if keys.is_empty() {
+                    "(unbound)".to_string()
+                } else {
+                    keys.join("/")
+                };
+                (keys, *description)
+            })
+            .collect();
+        let width = rows
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+        rows.into_iter()
+            .map(|(keys, description)| format!("  {:<width$} - {}", keys, description))
+            .collect()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::with_overrides(&[]).expect("the default keymap is valid")
+    }
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let map: BTreeMap<String, Keys> = BTreeMap::deserialize(deserializer)?;
+        let overrides: Vec<(String, Vec<String>)> = map
+            .into_iter()
+            .map(|(action, keys)| match keys {
+                Keys::One(key) => (action, vec![key]),
+                Keys::Many(keys) => (action, keys),
+            })
+            .collect();
+        Self::with_overrides(&overrides).map_err(|e| serde::de::Error::custom(format!("{:#}", e)))
+    }
+}
+
+impl Serialize for Keymap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let map: BTreeMap<&str, Vec<String>> = ACTIONS
+            .iter()
+            .map(|(action, name, _, _)| {
+                let keys = self.keys(*action).iter().map(|c| c.to_string()).collect();
+                (*name, keys)
+            })
+            .collect();
+        map.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap_overrides_and_collisions() {
+        let chord = |spec| KeyChord::parse(spec).unwrap();
+        assert_eq!(
+            chord("ctrl-J"),
+            KeyChord::from_event(&KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            chord("Shift-Tab"),
+            KeyChord::from_event(&KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT))
+        );
+        assert_eq!(
+            chord("G"),
+            KeyChord::from_event(&KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT))
+        );
+        assert_eq!(chord("alt--").code, KeyCode::Char('-'));
+        assert_eq!(chord("f12").to_string(), "F12");
+        assert!(KeyChord::parse("ctrl-nope").is_err());
+
+        // Defaults don't collide with each other
+        let keymap = Keymap::default();
+        let mut seen = std::collections::HashSet::new();
+        assert!(keymap
+            .bindings
+            .iter()
+            .all(|(chord, action)| seen.insert((*chord, action.is_editing()))));
+
+        // A configured key is taken from the action that had it by default
+        let keymap = Keymap::with_overrides(&[
+            ("pin".to_string(), vec!["j".to_string()]),
+            ("sort".to_string(), vec!["X".to_string()]),
+        ])
+        .unwrap();
+        assert_eq!(keymap.action(&chord("j")), Some(Action::Pin));
+        assert_eq!(keymap.action(&chord("m")), None);
+        assert_eq!(keymap.keys(Action::Down), [chord("Down"), chord("ctrl-j")]);
+        assert_eq!(keymap.hint(Action::Down, true).as_deref(), Some("Down"));
+
+        let error = Keymap::with_overrides(&[
+            ("quit".to_string(), vec!["x".to_string()]),
+            ("help".to_string(), vec!["x".to_string()]),
+        ])
+        .unwrap_err();
+        assert!(
+            error.to_string().contains("`x` is bound to both"),
+            "{}",
+            error
+        );
+        assert!(Keymap::with_overrides(&[("fly".to_string(), vec![])]).is_err());
+
+        // Ctrl-c is fixed, and quit always keeps a key
+        let keymap = Keymap::with_overrides(&[("copy".to_string(), vec!["c".to_string()])]);
+        assert_eq!(keymap.unwrap().action(&chord("c")), Some(Action::Copy));
+        let error = Keymap::with_overrides(&[("copy".to_string(), vec!["Ctrl-c".to_string()])])
+            .unwrap_err();
+        assert!(error.to_string().contains("always quits"), "{}", error);
+        let error = Keymap::with_overrides(&[("quit".to_string(), vec![])]).unwrap_err();
+        assert!(
+            error.to_string().contains("`quit` has no keys"),
+            "{}",
+            error
+        );
+        let error =
+            Keymap::with_overrides(&[("help".to_string(), vec!["q".to_string()])]).unwrap_err();
+        assert!(
+            error.to_string().contains("`quit` has no keys"),
+            "{}",
+            error
+        );
+
+        // The search box has keys of its own
+        let keymap = Keymap::with_overrides(&[(
+            "clear_line".to_string(),
+            vec!["Ctrl-k".to_string(), "Ctrl-u".to_string()],
+        )])
+        .unwrap();
+        assert_eq!(
+            keymap.editing_action(&chord("Ctrl-k")),
+            Some(Action::ClearLine)
+        );
+        assert_eq!(keymap.action(&chord("Ctrl-k")), Some(Action::Up));
+        assert_eq!(
+            keymap.editing_action(&chord("Left")),
+            Some(Action::CursorLeft)
+        );
+        assert_eq!(keymap.action(&chord("Left")), Some(Action::Left));
+        assert!(keymap
+            .help_lines(true)
+            .iter()
+            .any(|l| l.contains("Ctrl-k/Ctrl-u")));
+        assert!(keymap
+            .help_lines(false)
+            .iter()
+            .all(|l| !l.contains("Clear the query")));
+    }
+}
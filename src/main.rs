@@ -1,14 +1,32 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use std::collections::HashMap;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
+mod appearance;
 mod config;
+mod diagnostics;
+mod free;
+mod hyprlang;
+mod init;
+mod input;
+mod keyboard;
+mod keymap;
 mod parser;
+mod paths;
+mod state;
+#[cfg(test)]
+mod testutil;
 mod theme;
+mod themes;
 mod tui;
+mod watcher;
 
-use config::Config;
+use config::{Config, ThemeSettings};
 use parser::HyprlandParser;
+use state::State;
+use theme::{ColorChoice, ColorDepth};
 use tui::App;
 
 #[derive(Parser)]
@@ -17,20 +35,228 @@ struct Cli {
     /// Path to configuration file
     #[arg(short, long)]
     config: Option<PathBuf>,
+
+    /// Start on the "Pinned" tab
+    #[arg(long)]
+    pinned: bool,
+
+    /// Set a Hyprland variable for `# hyprlang if`, overriding the files
+    #[arg(long = "define", value_name = "NAME=VALUE", value_parser = parse_define, global = true)]
+    defines: Vec<(String, String)>,
+
+    /// When to use colors; `auto` follows NO_COLOR, COLORTERM and TERM
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto, global = true)]
+    color: ColorChoice,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List keys that have no binding for a modifier combination
+    Free {
+        /// Modifiers, e.g. `SUPER SHIFT` or `SUPER+SHIFT`; none for unmodified keys
+        modifiers: Vec<String>,
+
+        /// Look in this submap instead of the global bindings
+        #[arg(long)]
+        submap: Option<String>,
+    },
+
+    /// Report problems in the Hyprland keybind files; exits non-zero if any
+    /// errors are found
+    Check {
+        /// Also exit non-zero on warnings
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+
+    /// Write a showkey.yaml for the Hyprland config found on this machine
+    Init {
+        /// Where to write it [default: ~/.config/hypr-showkey/showkey.yaml]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Replace an existing file
+        #[arg(long)]
+        force: bool,
+
+        /// Theme preset to use
+        #[arg(long, default_value = "catppuccin_mocha")]
+        theme: String,
+    },
+
+    /// List the built-in themes and theme files with a preview of each
+    Themes,
+}
+
+fn parse_define(define: &str) -> Result<(String, String), String> {
+    let (name, value) = define
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got `{}`", define))?;
+    Ok((name.trim_start_matches('$').to_string(), value.to_string()))
+}
+
+/// The colors to use on a stream; `auto` means none when it isn't a terminal.
+fn color_depth(choice: ColorChoice, is_terminal: bool) -> ColorDepth {
+    if choice == ColorChoice::Auto && !is_terminal {
+        return ColorDepth::None;
+    }
+    ColorDepth::detect(choice, |name| std::env::var(name).ok())
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let defines: HashMap<String, String> = cli.defines.into_iter().collect();
+
+    if let Some(Commands::Init {
+        output,
+        force,
+        theme,
+    }) = cli.command
+    {
+        // Discover the files from Hyprland's config, not an existing showkey.yaml
+        let theme = init::theme_name(&theme)?;
+        let config = Config::default();
+        let output_file = match output {
+            Some(path) => path,
+            None => dirs::config_dir()
+                .context("Could not find config directory")?
+                .join("hypr-showkey/showkey.yaml"),
+        };
+        init::check_target(&output_file, force)?;
+        let parsed = HyprlandParser::new(&config).with_defines(defines).parse()?;
+        let hypr_dir = dirs::config_dir()
+            .context("Could not find config directory")?
+            .join("hypr");
+        let content = init::generate(&parsed.keybindings, &parsed.files, &hypr_dir, &theme);
+        init::write(&output_file, &content)?;
+        println!(
+            "Wrote {} ({} file(s), {} keybindings)",
+            output_file.display(),
+            parsed.files.len(),
+            parsed.keybindings.len()
+        );
+        return Ok(());
+    }
 
     // Load configuration
-    let config = Config::load(cli.config)?;
+    let config_file = Config::locate(cli.config)?;
+    // Only the TUI asks the terminal for its background, before taking it
+    // over; the subcommands go by what is known without asking
+    let config = if cli.command.is_none() {
+        Config::load(config_file.as_deref(), appearance::detect)?
+    } else {
+        Config::load(config_file.as_deref(), appearance::known)?
+    };
+    for warning in &config.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    if let Some(Commands::Themes) = cli.command {
+        let depth = color_depth(cli.color, std::io::stdout().is_terminal());
+        for entry in themes::list() {
+            let current = if entry.name == config.ui.theme.name {
+                "*"
+            } else {
+                " "
+            };
+            let source = entry
+                .path
+                .as_ref()
+                .map_or_else(|| "built-in".to_string(), |path| path.display().to_string());
+            println!("{} {:<22} {}", current, entry.name, source);
+            if depth != ColorDepth::None {
+                match themes::load(&entry.name) {
+                    Ok(theme) => println!(
+                        "  {}",
+                        themes::preview(
+                            &ThemeSettings::from_loaded(entry.name.clone(), theme),
+                            depth
+                        )
+                    ),
+                    Err(e) => println!("  {:#}", e),
+                }
+            }
+        }
+        return Ok(());
+    }
 
     // Parse Hyprland configuration files
-    let parser = HyprlandParser::new(&config);
-    let keybindings = parser.parse()?;
+    let parser = HyprlandParser::new(&config).with_defines(defines.clone());
+    let output = parser.parse()?;
+    for warning in &output.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    let keybindings = output.keybindings;
+
+    if let Some(Commands::Free { modifiers, submap }) = cli.command {
+        let modifiers = parser::parse_modifiers(&modifiers.join(" "));
+        let result = free::find_free_keys(&keybindings, &modifiers, submap.as_deref());
+        println!(
+            "Free keys for {} ({} free):",
+            if modifiers.is_empty() {
+                "no modifiers".to_string()
+            } else {
+                modifiers.join(" + ")
+            },
+            result.free_count()
+        );
+        for line in result.report() {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Check { deny_warnings }) = cli.command {
+        let color = color_depth(cli.color, std::io::stderr().is_terminal()) != ColorDepth::None;
+        for diagnostic in &output.diagnostics {
+            eprintln!("{}", diagnostic.render(color));
+        }
+
+        let theme = &config.ui.theme;
+        if let Some(detected) = theme.appearance {
+            println!(
+                "Theme {} for a {} background ({})",
+                theme.name,
+                detected.appearance.label(),
+                detected.source
+            );
+        }
+        if !theme.overrides.is_empty() {
+            println!(
+                "Theme {} with {} overridden",
+                theme.name,
+                theme.overrides.join(", ")
+            );
+        }
+
+        let Some(summary) = diagnostics::count_summary(&output.diagnostics) else {
+            println!(
+                "No problems found in {} file(s), {} keybindings",
+                output.files.len(),
+                keybindings.len()
+            );
+            return Ok(());
+        };
+        eprintln!("{} in {} file(s)", summary, output.files.len());
+        if diagnostics::check_fails(&output.diagnostics, deny_warnings) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     // Start TUI
-    let mut app = App::new(keybindings, &config);
+    let mut app = App::new(keybindings, &config, State::load());
+    app.set_color_depth(ColorDepth::detect(cli.color, |name| {
+        std::env::var(name).ok()
+    }));
+    app.set_diagnostics(output.diagnostics);
+    app.watch(config_file, &output.files, defines);
+    if cli.pinned {
+        app.show_pinned();
+    }
     app.run()?;
 
     Ok(())
@@ -1,114 +1,716 @@
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::config::Config;
+use crate::diagnostics::{Diagnostic, Location};
+use crate::hyprlang::{self, Directive, Entry, Node};
+use crate::paths;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Keybinding {
     pub key: String,
     pub action: String,
     pub description: String,
     pub category: String,
     pub raw_command: String,
+    /// The `submap` the binding belongs to, if any.
+    pub submap: Option<String>,
+    /// The letters after `bind`, e.g. `el` for `bindel`.
+    pub flags: String,
+    /// Where the bind is defined.
+    pub location: Location,
+    /// Inside a `# hyprlang if` block that is false with the current
+    /// variables, so Hyprland ignores it.
+    pub inactive: bool,
+    /// Marked `@hidden` in its comments: still counted as taken, but not
+    /// listed.
+    pub hidden: bool,
+}
+
+impl Keybinding {
+    /// The normalized, sorted modifier names, e.g. `["SHIFT", "SUPER"]`.
+    pub fn modifiers(&self) -> Vec<String> {
+        let parts: Vec<&str> = self.key.split(" + ").collect();
+        parse_modifiers(&parts[..parts.len() - 1].join(" "))
+    }
+
+    /// The normalized key name without modifiers, e.g. `RETURN`.
+    pub fn key_name(&self) -> String {
+        normalize_key(self.key.rsplit(" + ").next().unwrap_or_default())
+    }
+
+    /// The dispatcher part of the action, e.g. `exec`.
+    pub fn dispatcher(&self) -> &str {
+        self.action.split(',').next().unwrap_or_default().trim()
+    }
+
+    /// A stable identity for remembering this binding across launches and
+    /// config edits: the normalized modifier set, key, submap and dispatcher.
+    pub fn id(&self) -> String {
+        format!(
+            "{}|{}|{}|{}",
+            self.modifiers().join("+"),
+            self.key_name(),
+            self.submap.as_deref().unwrap_or_default(),
+            self.dispatcher().to_lowercase()
+        )
+    }
+}
+
+/// Parse a modifier list as written in a bind (`SUPER SHIFT`, `SUPER_SHIFT`,
+/// `SUPER+SHIFT`) into normalized, sorted, deduplicated names.
+pub fn parse_modifiers(modifiers: &str) -> Vec<String> {
+    let mut modifiers: Vec<String> = modifiers
+        .replace("$mainMod", "SUPER")
+        .replace("$shiftMod", "SHIFT")
+        .split(|c: char| c.is_whitespace() || c == '_' || c == '+')
+        .filter(|m| !m.is_empty())
+        .map(normalize_modifier)
+        .collect();
+    modifiers.sort();
+    modifiers.dedup();
+    modifiers
+}
+
+/// Map the different spellings Hyprland accepts for a modifier onto one name.
+pub fn normalize_modifier(modifier: &str) -> String {
+    let upper = modifier.trim().to_uppercase();
+    match upper.as_str() {
+        "WIN" | "LOGO" | "MOD4" | "META" => "SUPER".to_string(),
+        "CONTROL" => "CTRL".to_string(),
+        "MOD1" => "ALT".to_string(),
+        _ => upper,
+    }
+}
+
+/// Map a key name onto an upper-case keysym so that e.g. `Return`, `RETURN`
+/// and `enter` compare equal. Number row keycodes (`code:10` to `code:19`)
+/// become their digits.
+pub fn normalize_key(key: &str) -> String {
+    let upper = key.trim().to_uppercase();
+    if let Some(code) = upper.strip_prefix("CODE:") {
+        if let Ok(code @ 10..=19) = code.trim().parse::<u32>() {
+            return ((code - 9) % 10).to_string();
+        }
+    }
+    match upper.as_str() {
+        "ENTER" => "RETURN".to_string(),
+        "ESC" => "ESCAPE".to_string(),
+        "PAGE_UP" | "PAGEUP" => "PRIOR".to_string(),
+        "PAGE_DOWN" | "PAGEDOWN" => "NEXT".to_string(),
+        "PRINTSCREEN" => "PRINT".to_string(),
+        "DEL" => "DELETE".to_string(),
+        _ => upper,
+    }
+}
+
+/// Everything read from the Hyprland configuration.
+#[derive(Debug, Default)]
+pub struct ParseOutput {
+    pub keybindings: Vec<Keybinding>,
+    /// Every file that was read, including the ones pulled in by `source`.
+    pub files: Vec<PathBuf>,
+    /// Lines that couldn't be understood, and binds that look wrong.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Configured files that were skipped; the caller reports them.
+    pub warnings: Vec<String>,
+}
+
+/// The letters Hyprland accepts after `bind`, e.g. `bindel`.
+const BIND_FLAGS: &str = "lrcgoenmtisdpu";
+
+/// Hyprland's built-in dispatchers. Plugin dispatchers contain a `:` and
+/// aren't checked.
+const KNOWN_DISPATCHERS: &[&str] = &[
+    "exec",
+    "execr",
+    "pass",
+    "sendshortcut",
+    "sendkeystate",
+    "killactive",
+    "forcekillactive",
+    "closewindow",
+    "killwindow",
+    "signal",
+    "signalwindow",
+    "workspace",
+    "movetoworkspace",
+    "movetoworkspacesilent",
+    "togglefloating",
+    "setfloating",
+    "settiled",
+    "fullscreen",
+    "fullscreenstate",
+    "dpms",
+    "forceidle",
+    "pin",
+    "movefocus",
+    "movewindow",
+    "resizewindow",
+    "swapwindow",
+    "centerwindow",
+    "resizeactive",
+    "moveactive",
+    "resizewindowpixel",
+    "movewindowpixel",
+    "cyclenext",
+    "swapnext",
+    "tagwindow",
+    "focuswindow",
+    "focusmonitor",
+    "splitratio",
+    "movecursortocorner",
+    "movecursor",
+    "renameworkspace",
+    "exit",
+    "forcerendererreload",
+    "movecurrentworkspacetomonitor",
+    "focusworkspaceoncurrentmonitor",
+    "moveworkspacetomonitor",
+    "swapactiveworkspaces",
+    "bringactivetotop",
+    "alterzorder",
+    "togglespecialworkspace",
+    "focusurgentorlast",
+    "togglegroup",
+    "changegroupactive",
+    "focuscurrentorlast",
+    "lockgroups",
+    "lockactivegroup",
+    "moveintogroup",
+    "moveoutofgroup",
+    "movewindoworgroup",
+    "movegroupwindow",
+    "denywindowfromgroup",
+    "setignoregrouplock",
+    "global",
+    "submap",
+    "event",
+    "setprop",
+    "toggleswallow",
+    "layoutmsg",
+    "pseudo",
+    "togglesplit",
+    "swapsplit",
+    "preselect",
+    "movetoroot",
+];
+
+/// Variables the parser substitutes itself when they aren't defined.
+const BUILTIN_VARIABLES: &[&str] = &["mainMod", "shiftMod"];
+
+/// A problem inside a bind's value, as a byte range of the raw value.
+struct ValueError {
+    offset: usize,
+    len: usize,
+    message: String,
+}
+
+impl ValueError {
+    fn new(offset: usize, len: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            len,
+            message: message.into(),
+        }
+    }
+}
+
+/// Resolve the path of a `source` directive: `~` is the home directory and
+/// relative paths are relative to the file containing the directive.
+fn resolve_source_path(from: &Path, source: &str) -> PathBuf {
+    let path = match source.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(source),
+    };
+    if path.is_absolute() {
+        path
+    } else {
+        from.parent().unwrap_or(Path::new(".")).join(path)
+    }
+}
+
+/// `$name` references in `text`, as (byte offset, name).
+fn variable_references(text: &str) -> Vec<(usize, &str)> {
+    let mut references = Vec::new();
+    for (offset, _) in text.match_indices('$') {
+        let name_start = offset + 1;
+        let name_len = text[name_start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(text.len() - name_start);
+        if name_len > 0 {
+            references.push((offset, &text[name_start..name_start + name_len]));
+        }
+    }
+    references
+}
+
+/// Replace the defined `$name` variables in `text`. Undefined ones are left
+/// alone, like Hyprland does, so `exec` commands can use shell variables.
+fn expand_variables(text: &str, variables: &HashMap<String, String>) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (offset, name) in variable_references(text) {
+        if let Some(value) = variables.get(name) {
+            expanded.push_str(&text[copied..offset]);
+            expanded.push_str(value);
+            copied = offset + 1 + name.len();
+        }
+    }
+    expanded.push_str(&text[copied..]);
+    expanded
+}
+
+/// Where in `path` a column of `entry`'s line is.
+fn location(path: &Path, entry: &Entry, column: usize) -> Location {
+    Location {
+        file: path.to_path_buf(),
+        line: entry.line,
+        column,
+    }
+}
+
+/// Number of characters in `text[offset..offset + len]`.
+fn width_at(text: &str, offset: usize, len: usize) -> usize {
+    let start = offset.min(text.len());
+    let end = (offset + len).min(text.len());
+    text.get(start..end).map_or(1, |s| s.chars().count())
+}
+
+/// The title of a section comment such as `=== Workspaces ===` or
+/// `### KEYBINDINGS ###`.
+fn section_title(comment: &str) -> Option<String> {
+    const RULE: &[char] = &['=', '#', '-', '*'];
+    let leading = comment.len() - comment.trim_start_matches(RULE).len();
+    let trailing = comment.len() - comment.trim_end_matches(RULE).len();
+    let title = comment.trim_matches(RULE).trim();
+    (leading >= 2 && trailing >= 2 && title.chars().any(char::is_alphanumeric))
+        .then(|| title.to_string())
+}
+
+/// Commented-out config lines such as `# bind = SUPER, Q, killactive`,
+/// which shouldn't end up in a description.
+fn looks_like_code(comment: &str) -> bool {
+    comment.split_once('=').is_some_and(|(key, _)| {
+        let key = key.trim();
+        key.starts_with('$') || hyprlang::is_keyword(key)
+    })
+}
+
+/// What the comments around a bind say about it.
+#[derive(Default)]
+struct DocComment {
+    /// `@desc ...`
+    description: Option<String>,
+    /// The comment at the end of the bind line.
+    inline: Option<String>,
+    /// The plain text lines of the comment block above the bind.
+    text: Vec<String>,
+    /// `@category ...`
+    category: Option<String>,
+    /// `@hidden`
+    hidden: bool,
+}
+
+/// Whether a variable counts as true in `# hyprlang if`: it must be
+/// defined and not empty, `0`, `false`, `no` or `off`.
+fn is_truthy(value: Option<&String>) -> bool {
+    value.is_some_and(|value| {
+        !matches!(
+            value.trim().to_lowercase().as_str(),
+            "" | "0" | "false" | "no" | "off"
+        )
+    })
 }
 
 pub struct HyprlandParser<'a> {
     config: &'a Config,
+    /// Variables set with `--define`, overriding the files.
+    defines: HashMap<String, String>,
+}
+
+/// What has been read so far while parsing, shared across sourced files.
+#[derive(Default)]
+struct ParseState {
+    output: ParseOutput,
+    variables: HashMap<String, String>,
+}
+
+/// Per-file parsing state.
+#[derive(Default)]
+struct FileScope {
+    submap: Option<String>,
+    /// Open `# hyprlang if` blocks: whether each is true, and its line.
+    conditions: Vec<(bool, usize)>,
+    /// Set when the file was sourced from a false `# hyprlang if` block.
+    sourced_inactive: bool,
+    /// Start of the current `# hyprlang noerror true` block.
+    noerror_since: Option<usize>,
+    /// Line ranges whose errors are suppressed.
+    noerror: Vec<std::ops::RangeInclusive<usize>>,
+    /// The last section comment, e.g. `Workspaces` for `# === Workspaces ===`.
+    section: Option<String>,
+}
+
+impl FileScope {
+    fn inactive(&self) -> bool {
+        self.sourced_inactive || self.conditions.iter().any(|(active, _)| !active)
+    }
 }
 
 impl<'a> HyprlandParser<'a> {
     pub fn new(config: &'a Config) -> Self {
-        Self { config }
+        Self {
+            config,
+            defines: HashMap::new(),
+        }
     }
 
-    pub fn parse(&self) -> Result<Vec<Keybinding>> {
-        let config_paths = self.config.resolve_hyprland_paths()?;
-        let mut keybindings = Vec::new();
+    /// Set variables as if they were defined before the first file and
+    /// ignore the files' own definitions of them, e.g. to see the desktop
+    /// half of a config on a laptop.
+    pub fn with_defines(mut self, defines: HashMap<String, String>) -> Self {
+        self.defines = defines;
+        self
+    }
+
+    pub fn parse(&self) -> Result<ParseOutput> {
+        let mut warnings = Vec::new();
+        let config_paths = self.config.resolve_hyprland_paths(&mut warnings)?;
+        let mut state = ParseState {
+            variables: self.defines.clone(),
+            ..ParseState::default()
+        };
 
+        // Files are parsed in order so an `unbind` only removes the binds
+        // that came before it, like Hyprland does
         for path in config_paths {
-            let bindings = self.parse_file(&path)?;
-            keybindings.extend(bindings);
+            self.parse_file(&path, &mut state, false)?;
         }
 
-        Ok(keybindings)
+        let mut output = state.output;
+        output.warnings = warnings;
+        output
+            .diagnostics
+            .extend(find_conflicts(&output.keybindings));
+        Ok(output)
     }
 
-    fn parse_file(&self, path: &Path) -> Result<Vec<Keybinding>> {
+    fn parse_file(&self, path: &Path, state: &mut ParseState, inactive: bool) -> Result<()> {
+        // A file sourced twice (or sourcing itself) is only read once
+        if state.output.files.iter().any(|f| f == path) {
+            return Ok(());
+        }
+        state.output.files.push(path.to_path_buf());
+
         let content =
             fs::read_to_string(path).with_context(|| format!("Failed to read file: {:?}", path))?;
 
-        let mut keybindings = Vec::new();
+        let (document, errors) = hyprlang::parse(&content);
+        for error in errors {
+            let location = Location {
+                file: path.to_path_buf(),
+                line: error.line,
+                column: error.column,
+            };
+            state.output.diagnostics.push(Diagnostic::error(
+                error.message,
+                location,
+                error.len,
+                &error.source_line,
+            ));
+        }
 
-        for line in content.lines() {
-            let line = line.trim();
+        let mut scope = FileScope {
+            sourced_inactive: inactive,
+            ..FileScope::default()
+        };
+        self.parse_nodes(path, &document.nodes, state, &mut scope)?;
 
-            // Skip empty lines and comments that aren't inline
-            if line.is_empty() || (line.starts_with('#') && !line.contains("bind")) {
-                continue;
+        for &(_, line) in &scope.conditions {
+            state.output.diagnostics.push(Diagnostic::error(
+                "unclosed `# hyprlang if`",
+                Location {
+                    file: path.to_path_buf(),
+                    line,
+                    column: 1,
+                },
+                content.lines().nth(line - 1).unwrap_or_default().len(),
+                content.lines().nth(line - 1).unwrap_or_default(),
+            ));
+        }
+        if let Some(start) = scope.noerror_since {
+            scope.noerror.push(start..=usize::MAX);
+        }
+        state.output.diagnostics.retain(|d| {
+            d.location.file != path || !scope.noerror.iter().any(|r| r.contains(&d.location.line))
+        });
+
+        Ok(())
+    }
+
+    fn parse_nodes(
+        &self,
+        path: &Path,
+        nodes: &[Node],
+        state: &mut ParseState,
+        scope: &mut FileScope,
+    ) -> Result<()> {
+        for node in nodes {
+            let comments = match node {
+                Node::Comment(comment) => std::slice::from_ref(comment),
+                Node::Variable(entry)
+                | Node::Keyword(entry)
+                | Node::Source(entry)
+                | Node::Assignment(entry) => &entry.doc[..],
+                Node::Category(category) => &category.doc[..],
+                Node::Directive { .. } => &[],
+            };
+            if let Some(title) = comments.iter().rev().find_map(|c| section_title(&c.text)) {
+                scope.section = Some(title);
             }
 
-            if let Some(binding) = self.parse_bind_line(line) {
-                keybindings.push(binding);
+            match node {
+                Node::Directive { directive, line } => {
+                    self.apply_directive(path, directive, *line, state, scope)
+                }
+                // Statements in a false `# hyprlang if` block have no
+                // effect, but their binds are still listed
+                _ if scope.inactive() && !matches!(node, Node::Keyword(_) | Node::Category(_)) => {}
+                Node::Variable(entry) if !self.defines.contains_key(&entry.key) => {
+                    let value = expand_variables(&entry.value(), &state.variables);
+                    state.variables.insert(entry.key.clone(), value);
+                }
+                // `source = file` is parsed in place, like Hyprland does
+                // A glob sources every matching file, in path order
+                Node::Source(entry) => {
+                    let source = expand_variables(&entry.value(), &state.variables);
+                    let source_path = resolve_source_path(path, &source);
+                    let (sources, problem) = if paths::is_glob(&source) {
+                        (paths::glob(&source_path), "no sourced files match")
+                    } else {
+                        let exists = source_path.exists();
+                        (
+                            exists.then(|| source_path.clone()).into_iter().collect(),
+                            "sourced file not found",
+                        )
+                    };
+                    if sources.is_empty() {
+                        state.output.diagnostics.push(Diagnostic::warning(
+                            format!("{}: {}", problem, source_path.display()),
+                            location(path, entry, entry.value_column),
+                            entry.raw_value.chars().count(),
+                            &entry.source_line,
+                        ));
+                    }
+                    for source_path in sources {
+                        self.parse_file(&source_path, state, scope.inactive())?;
+                    }
+                }
+                // Track `submap = name` ... `submap = reset` blocks
+                Node::Keyword(entry) if entry.key == "submap" && !scope.inactive() => {
+                    let name = entry.value();
+                    scope.submap = (!name.is_empty() && name != "reset").then_some(name);
+                }
+                Node::Keyword(entry) if entry.key == "unbind" && !scope.inactive() => {
+                    let value = expand_variables(&entry.value(), &state.variables);
+                    if let Some((modifiers, key)) = value.split_once(',') {
+                        let (modifiers, key) = (parse_modifiers(modifiers), normalize_key(key));
+                        state
+                            .output
+                            .keybindings
+                            .retain(|kb| kb.modifiers() != modifiers || kb.key_name() != key);
+                    }
+                }
+                Node::Keyword(entry) if entry.key.starts_with("bind") => {
+                    if let Some(mut binding) = self.parse_bind(path, entry, state, scope) {
+                        binding.submap = scope.submap.clone();
+                        state.output.keybindings.push(binding);
+                    }
+                }
+                Node::Category(category) => {
+                    self.parse_nodes(path, &category.body, state, scope)?;
+                }
+                _ => {}
             }
         }
 
-        Ok(keybindings)
+        Ok(())
     }
 
-    fn parse_bind_line(&self, line: &str) -> Option<Keybinding> {
-        // Handle comments - extract the comment part
-        let (bind_part, comment) = if let Some(comment_pos) = line.find('#') {
-            let bind_part = line[..comment_pos].trim();
-            let comment = line[comment_pos + 1..].trim();
-            (bind_part, Some(comment))
-        } else {
-            (line, None)
+    fn apply_directive(
+        &self,
+        path: &Path,
+        directive: &Directive,
+        line: usize,
+        state: &mut ParseState,
+        scope: &mut FileScope,
+    ) {
+        match directive {
+            Directive::If { variable, negated } => {
+                let value = is_truthy(state.variables.get(variable));
+                scope.conditions.push((value != *negated, line));
+            }
+            Directive::EndIf => {
+                if scope.conditions.pop().is_none() {
+                    state.output.diagnostics.push(Diagnostic::error(
+                        "`# hyprlang endif` without `# hyprlang if`",
+                        Location {
+                            file: path.to_path_buf(),
+                            line,
+                            column: 1,
+                        },
+                        "# hyprlang endif".len(),
+                        "# hyprlang endif",
+                    ));
+                }
+            }
+            Directive::NoError(true) => {
+                scope.noerror_since.get_or_insert(line);
+            }
+            Directive::NoError(false) => {
+                if let Some(start) = scope.noerror_since.take() {
+                    scope.noerror.push(start..=line);
+                }
+            }
+        }
+    }
+
+    /// Parse a `bind[flags] = MODS, key, dispatcher, params` entry, reporting
+    /// problems to `state`.
+    fn parse_bind(
+        &self,
+        path: &Path,
+        entry: &Entry,
+        state: &mut ParseState,
+        scope: &FileScope,
+    ) -> Option<Keybinding> {
+        let inactive = scope.inactive();
+        let doc = self.read_doc(path, entry, &mut state.output.diagnostics);
+        let ParseState {
+            output, variables, ..
+        } = state;
+        let diagnostics = &mut output.diagnostics;
+        let raw = entry.raw_value.as_str();
+        let error = |offset: usize, len: usize, message: String| {
+            Diagnostic::error(
+                message,
+                location(path, entry, entry.value_column_at(offset)),
+                width_at(raw, offset, len),
+                &entry.source_line,
+            )
         };
 
-        // Parse bind commands: bind = modifiers, key, action, params
-        if !bind_part.starts_with("bind") {
+        let flags = &entry.key["bind".len()..];
+        if let Some(position) = flags.find(|c: char| !BIND_FLAGS.contains(c)) {
+            diagnostics.push(Diagnostic::error(
+                format!("unknown bind flag `{}`", &flags[position..position + 1]),
+                location(path, entry, entry.key_column + "bind".len() + position),
+                1,
+                &entry.source_line,
+            ));
             return None;
         }
 
-        // Skip unbind commands (they remove keybindings)
-        if bind_part.starts_with("unbind") {
+        // The format is: modifiers, key, action, [params...]; with the `d`
+        // flag a description comes before the action
+        let has_description = flags.contains('d');
+        let action_index = if has_description { 3 } else { 2 };
+        let parts = match self.split_bind_parts(raw, action_index + 2) {
+            Ok(parts) => parts,
+            Err(e) => {
+                diagnostics.push(error(e.offset, e.len, e.message));
+                return None;
+            }
+        };
+        if parts.len() <= action_index {
+            let expected = if has_description {
+                "expected `MODS, key, description, dispatcher[, params]`"
+            } else {
+                "expected `MODS, key, dispatcher[, params]`"
+            };
+            diagnostics.push(error(0, raw.len(), expected.to_string()));
             return None;
         }
 
-        // Extract the part after "bind" or "binde" or "bindm"
-        let after_bind = if bind_part.starts_with("binde") {
-            bind_part.strip_prefix("binde")?
-        } else if bind_part.starts_with("bindm") {
-            bind_part.strip_prefix("bindm")?
-        } else {
-            bind_part.strip_prefix("bind")?
+        let field = |index: usize| -> String {
+            parts
+                .get(index)
+                .map(|(_, part)| expand_variables(&hyprlang::unescape(part), variables))
+                .unwrap_or_default()
         };
+        let modifiers = field(0);
+        let key = field(1);
+        let action = field(action_index);
+        let params = field(action_index + 1);
 
-        // Handle the = sign properly
-        let bind_content = after_bind.trim();
-        let bind_content = if let Some(stripped) = bind_content.strip_prefix('=') {
-            stripped.trim()
-        } else {
-            bind_content
-        };
-
-        // The format is: modifiers, key, action, [params...]
-        // Split by commas, but be careful with nested commas in exec commands
-        let parts = self.split_bind_parts(bind_content);
-        if parts.len() < 3 {
+        if key.is_empty() {
+            diagnostics.push(error(parts[1].0, 1, "missing key".to_string()));
+            return None;
+        }
+        if action.is_empty() {
+            diagnostics.push(error(
+                parts[action_index].0,
+                1,
+                "missing dispatcher".to_string(),
+            ));
             return None;
         }
 
-        // Parse the parts correctly
-        let modifiers = parts[0].trim();
-        let key = parts[1].trim();
-        let action = parts[2].trim();
-        let params = if parts.len() > 3 {
-            parts[3..].join(",").trim().to_string()
+        // Warnings for binds that parse but probably don't do what was
+        // meant. Inactive binds often use variables from the other branch,
+        // so they are only checked for errors.
+        let mut warnings = Vec::new();
+        let warning = |offset: usize, len: usize, message: String| {
+            Diagnostic::warning(
+                message,
+                location(path, entry, entry.value_column_at(offset)),
+                width_at(raw, offset, len),
+                &entry.source_line,
+            )
+        };
+        let (dispatcher_offset, raw_dispatcher) = &parts[action_index];
+        if !action.contains(':') && !KNOWN_DISPATCHERS.contains(&action.as_str()) {
+            warnings.push(warning(
+                *dispatcher_offset,
+                raw_dispatcher.len(),
+                format!("unknown dispatcher `{}`", action),
+            ));
+        }
+        let is_exec = matches!(action.as_str(), "exec" | "execr");
+        if is_exec && params.is_empty() {
+            warnings.push(
+                warning(
+                    *dispatcher_offset,
+                    raw_dispatcher.len(),
+                    format!("`{}` with no command", action),
+                )
+                .with_note("the bind does nothing when pressed"),
+            );
+        }
+        // Commands run by `exec` go through a shell, so `$HOME` and
+        // friends are fine there
+        let checked = if is_exec {
+            action_index + 1
         } else {
-            String::new()
+            parts.len()
         };
+        for (offset, part) in &parts[..checked] {
+            for (reference, name) in variable_references(part) {
+                if !variables.contains_key(name) && !BUILTIN_VARIABLES.contains(&name) {
+                    warnings.push(warning(
+                        offset + reference,
+                        name.len() + 1,
+                        format!("undefined variable `${}`", name),
+                    ));
+                }
+            }
+        }
+        if !inactive {
+            diagnostics.extend(warnings);
+        }
 
         // Combine modifiers and key
         let modifiers_and_key = if modifiers.is_empty() {
@@ -117,78 +719,173 @@ impl<'a> HyprlandParser<'a> {
             format!("{} {}", modifiers, key)
         };
 
-        // Filter out empty or unbound keybindings
-        if action.is_empty() {
-            return None;
-        }
-
         // Format the key combination
         let formatted_key = self.format_key_combination(&modifiers_and_key);
 
-        // Create description from comment or action
-        let description = if let Some(comment) = comment {
-            comment.to_string()
-        } else {
-            self.generate_description(action, &params)
+        // Create description from `@desc`, the `d` flag's field, the
+        // comments or the action
+        let description = match (doc.description, field(2), doc.inline) {
+            (Some(description), _, _) => description,
+            (None, description, _) if has_description && !description.is_empty() => description,
+            (None, _, Some(comment)) => comment,
+            _ if !doc.text.is_empty() => doc.text.join(" "),
+            _ => self.generate_description(&action, &params),
         };
 
-        // Determine category
-        let category = self.determine_category(action, &params, &description);
+        // Determine category: `@category`, the section, or by keywords
+        let section = scope
+            .section
+            .clone()
+            .filter(|_| self.config.hyprland_configs.section_categories);
+        let category = doc
+            .category
+            .or(section)
+            .unwrap_or_else(|| self.determine_category(&action, &params, &description));
 
         // Create the full action string
         let full_action = if params.is_empty() {
-            action.to_string()
+            action
         } else {
             format!("{}, {}", action, params)
         };
 
         Some(Keybinding {
             key: formatted_key,
-            action: full_action.clone(),
+            action: full_action,
             description,
             category,
-            raw_command: format!("bind = {}", bind_content),
+            raw_command: format!("{} = {}", entry.key, raw),
+            submap: None,
+            flags: flags.to_string(),
+            location: location(path, entry, entry.key_column),
+            inactive,
+            hidden: doc.hidden,
         })
     }
 
-    fn split_bind_parts(&self, content: &str) -> Vec<String> {
+    /// Read the comment block above a bind and the comment after it. Lines
+    /// starting with `@` are annotations; the block's other lines describe
+    /// the bind unless they are section titles or commented-out code. Only
+    /// the first of several binds in a row has a block above it.
+    fn read_doc(
+        &self,
+        path: &Path,
+        entry: &Entry,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> DocComment {
+        let mut doc = DocComment::default();
+
+        // Only the lines after the last section title belong to the bind
+        let block = if self.config.hyprland_configs.doc_comments {
+            let start = entry
+                .doc
+                .iter()
+                .rposition(|c| section_title(&c.text).is_some())
+                .map_or(0, |i| i + 1);
+            &entry.doc[start..]
+        } else {
+            &[]
+        };
+        let inline = entry.comment.as_ref().map(|text| (text, entry.line));
+        let lines = block.iter().map(|c| (&c.text, c.line)).chain(inline);
+
+        for (text, line) in lines {
+            let Some(annotation) = text.strip_prefix('@') else {
+                if Some(line) == inline.map(|(_, line)| line) {
+                    doc.inline = Some(text.clone());
+                } else if !looks_like_code(text) {
+                    doc.text.push(text.clone());
+                }
+                continue;
+            };
+
+            let (name, value) = annotation
+                .split_once(char::is_whitespace)
+                .map_or((annotation, ""), |(name, value)| (name, value.trim()));
+            match name {
+                "category" if !value.is_empty() => doc.category = Some(value.to_string()),
+                "desc" | "description" if !value.is_empty() => {
+                    doc.description = Some(value.to_string())
+                }
+                "hidden" => doc.hidden = true,
+                _ => {
+                    let source = format!("# {}", text);
+                    diagnostics.push(Diagnostic::warning(
+                        format!("unknown or empty annotation `@{}`", name),
+                        Location {
+                            file: path.to_path_buf(),
+                            line,
+                            column: 3,
+                        },
+                        name.len() + 1,
+                        &source,
+                    ));
+                }
+            }
+        }
+
+        doc
+    }
+
+    /// Split a bind's value into at most `max_fields` trimmed fields, each
+    /// with its byte offset in `content`. Commas inside quotes or
+    /// parentheses don't split, and the last field keeps the rest of the
+    /// line, commas included.
+    fn split_bind_parts(
+        &self,
+        content: &str,
+        max_fields: usize,
+    ) -> Result<Vec<(usize, String)>, ValueError> {
         let mut parts = Vec::new();
-        let mut current = String::new();
-        let mut in_quotes = false;
-        let mut paren_depth = 0;
-        let chars = content.chars();
+        let mut start = 0;
+        let mut quote_start: Option<usize> = None;
+        let mut open_parens: Vec<usize> = Vec::new();
+
+        let field = |start: usize, end: usize| {
+            let field = &content[start..end];
+            let leading = field.len() - field.trim_start().len();
+            (start + leading, field.trim().to_string())
+        };
 
-        for ch in chars {
+        for (offset, ch) in content.char_indices() {
             match ch {
                 '"' => {
-                    in_quotes = !in_quotes;
-                    current.push(ch);
-                }
-                '(' => {
-                    paren_depth += 1;
-                    current.push(ch);
+                    quote_start = match quote_start {
+                        Some(_) => None,
+                        None => Some(offset),
+                    };
                 }
-                ')' => {
-                    paren_depth -= 1;
-                    current.push(ch);
+                '(' if quote_start.is_none() => open_parens.push(offset),
+                ')' if quote_start.is_none() => {
+                    let Some(_) = open_parens.pop() else {
+                        return Err(ValueError::new(offset, 1, "unmatched `)`"));
+                    };
                 }
-                ',' if !in_quotes && paren_depth == 0 => {
-                    if !current.trim().is_empty() {
-                        parts.push(current.trim().to_string());
-                    }
-                    current = String::new();
-                }
-                _ => {
-                    current.push(ch);
+                // Keep empty fields so `bind = , XF86AudioMute, ...` still
+                // has its (empty) modifiers in the first position
+                ',' if quote_start.is_none()
+                    && open_parens.is_empty()
+                    && parts.len() + 1 < max_fields =>
+                {
+                    parts.push(field(start, offset));
+                    start = offset + 1;
                 }
+                _ => {}
             }
         }
 
-        if !current.trim().is_empty() {
-            parts.push(current.trim().to_string());
+        if let Some(offset) = quote_start {
+            return Err(ValueError::new(offset, 1, "unterminated quote"));
+        }
+        if let Some(&offset) = open_parens.last() {
+            return Err(ValueError::new(offset, 1, "unclosed `(`"));
+        }
+
+        if !content[start..].trim().is_empty() {
+            parts.push(field(start, content.len()));
         }
 
-        parts
+        Ok(parts)
     }
 
     fn format_key_combination(&self, modifiers_and_key: &str) -> String {
@@ -259,3 +956,133 @@ impl<'a> HyprlandParser<'a> {
         "Other".to_string()
     }
 }
+
+/// Warnings for chords bound more than once with the same flags in the
+/// same submap, pointing at every bind after the first.
+fn find_conflicts(keybindings: &[Keybinding]) -> Vec<Diagnostic> {
+    // (modifiers, key, submap, flags)
+    type Chord<'a> = (Vec<String>, String, Option<&'a str>, &'a str);
+    let mut first: HashMap<Chord, &Keybinding> = HashMap::new();
+    let mut diagnostics = Vec::new();
+
+    for kb in keybindings.iter().filter(|kb| !kb.inactive) {
+        let chord = (
+            kb.modifiers(),
+            kb.key_name(),
+            kb.submap.as_deref(),
+            kb.flags.as_str(),
+        );
+        match first.get(&chord) {
+            Some(previous) => diagnostics.push(
+                Diagnostic::warning(
+                    format!("`{}` is already bound", kb.key),
+                    Location {
+                        column: 1,
+                        ..kb.location.clone()
+                    },
+                    kb.raw_command.chars().count(),
+                    &kb.raw_command,
+                )
+                .with_note(format!(
+                    "first bound at {} to `{}`",
+                    previous.location, previous.action
+                )),
+            ),
+            None => {
+                first.insert(chord, kb);
+            }
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    #[test]
+    fn test_defines_override_the_files() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/hyprlang/conditionals.conf");
+        let config: Config = serde_yaml::from_str(&format!(
+            "hyprland_configs:\n  files: [{:?}]\n",
+            path.display().to_string()
+        ))
+        .unwrap();
+        let defines = HashMap::from([("LAPTOP".to_string(), "0".to_string())]);
+        let output = HyprlandParser::new(&config)
+            .with_defines(defines)
+            .parse()
+            .unwrap();
+
+        let inactive = |key: &str| {
+            output
+                .keybindings
+                .iter()
+                .find(|kb| kb.key.ends_with(key))
+                .unwrap()
+                .inactive
+        };
+        assert!(inactive("XF86MonBrightnessUp"));
+        assert!(!inactive("F9"));
+
+        // With LAPTOP off, `submap = desktop` is live again (the golden
+        // file covers the false block)
+        let after = output
+            .keybindings
+            .iter()
+            .find(|kb| kb.key.ends_with(" G"))
+            .unwrap();
+        assert_eq!(after.submap.as_deref(), Some("desktop"));
+    }
+
+    /// Extract the keybindings from every file in `testdata/hyprlang` and
+    /// compare them with the `.binds` file next to it. Run with
+    /// `UPDATE_GOLDEN=1` to rewrite the expected files.
+    #[test]
+    fn test_golden_keybindings() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/hyprlang");
+        let home = dirs::home_dir().unwrap_or_default();
+
+        for path in testutil::testdata_files("hyprlang", "conf") {
+            let config: Config = serde_yaml::from_str(&format!(
+                "hyprland_configs:\n  files: [{:?}]\n",
+                path.display().to_string()
+            ))
+            .unwrap();
+            let output = HyprlandParser::new(&config).parse().unwrap();
+
+            let mut actual = String::new();
+            for kb in &output.keybindings {
+                actual.push_str(&format!(
+                    "{}:{} [{}] {} => {} | {} | {}{}\n",
+                    kb.location.line,
+                    kb.location.column,
+                    kb.submap.as_deref().unwrap_or_default(),
+                    kb.key,
+                    kb.action,
+                    kb.description,
+                    kb.flags,
+                    if kb.inactive { " (inactive)" } else { "" }
+                ));
+            }
+            for d in &output.diagnostics {
+                let message = d
+                    .message
+                    .replace(&dir.display().to_string(), ".")
+                    .replace(&home.display().to_string(), "~");
+                actual.push_str(&format!(
+                    "{}:{}: {}: {}\n",
+                    d.location.line,
+                    d.location.column,
+                    d.severity.label(),
+                    message
+                ));
+            }
+
+            testutil::assert_golden(&path.with_extension("binds"), &actual);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Expand a leading `~` and `$VAR` / `${VAR}` references in a configured
+/// path. `XDG_CONFIG_HOME` falls back to `~/.config` when it isn't set;
+/// other undefined variables are an error.
+pub fn expand(path: &str, env: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let home = || {
+        dirs::home_dir()
+            .map(|home| home.to_string_lossy().into_owned())
+            .context("Could not find home directory")
+    };
+    let lookup = |name: &str| -> Result<String> {
+        match env(name).filter(|value| !value.is_empty()) {
+            Some(value) => Ok(value),
+            None if name == "HOME" => home(),
+            None if name == "XDG_CONFIG_HOME" => Ok(format!("{}/.config", home()?)),
+            None => bail!("environment variable `{}` is not set", name),
+        }
+    };
+
+    let mut out = String::new();
+    let mut rest = path;
+    if rest == "~" || rest.starts_with("~/") {
+        out.push_str(&home()?);
+        rest = &rest[1..];
+    }
+
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+        let (name, after) = if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .with_context(|| format!("unclosed `${{` in {:?}", path))?;
+            (&braced[..end], &braced[end + 1..])
+        } else {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+        if name.is_empty() {
+            // A lone `$` is kept as it is
+            out.push('$');
+        } else {
+            out.push_str(&lookup(name)?);
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+
+    Ok(out)
+}
+
+/// Whether a path contains glob wildcards (`*`, `?` or `[...]`).
+pub fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// The files matching a glob pattern, sorted by path so the order doesn't
+/// depend on the filesystem. `*`, `?` and `[...]` don't match `/` or a
+/// leading `.`; a `**` component matches any number of directories. A
+/// pattern the glob crate can't parse matches nothing.
+pub fn glob(pattern: &Path) -> Vec<PathBuf> {
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: true,
+    };
+    let Ok(paths) = glob::glob_with(&pattern.to_string_lossy(), options) else {
+        return Vec::new();
+    };
+    let mut matches: Vec<PathBuf> = paths.flatten().filter(|path| path.is_file()).collect();
+    matches.sort();
+    matches.dedup();
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn test_expand_and_glob() {
+        let env = |name: &str| match name {
+            "DOTFILES" => Some("/dots".to_string()),
+            _ => None,
+        };
+        assert_eq!(expand("$DOTFILES/hypr", env).unwrap(), "/dots/hypr");
+        assert_eq!(expand("${DOTFILES}_old/$", env).unwrap(), "/dots_old/$");
+        assert!(expand("$NOPE/binds.conf", env).is_err());
+        assert!(expand("${DOTFILES/hypr", env).is_err());
+
+        let home = dirs::home_dir().unwrap().to_string_lossy().into_owned();
+        assert_eq!(
+            expand("~/binds.conf", env).unwrap(),
+            home.clone() + "/binds.conf"
+        );
+        assert_eq!(expand("~", env).unwrap(), home);
+        assert_eq!(expand("a/~/b", env).unwrap(), "a/~/b");
+        assert_eq!(
+            expand("$XDG_CONFIG_HOME/hypr", env).unwrap(),
+            home + "/.config/hypr"
+        );
+
+        let dir = TempDir::new("glob");
+        for file in [
+            "b.conf",
+            "a.conf",
+            "notes.txt",
+            ".hidden.conf",
+            "sub/c.conf",
+        ] {
+            dir.write(file, "");
+        }
+
+        assert_eq!(
+            glob(&dir.join("*.conf")),
+            [dir.join("a.conf"), dir.join("b.conf")]
+        );
+        assert_eq!(
+            glob(&dir.join("**/[a-c].conf")),
+            [
+                dir.join("a.conf"),
+                dir.join("b.conf"),
+                dir.join("sub/c.conf")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_glob_classes_and_escapes() {
+        let dir = TempDir::new("glob-class");
+        for file in [
+            "[x].conf",
+            "x].conf",
+            "a\\b.conf",
+            "a-.conf",
+            "b.conf",
+            ".hidden/d.conf",
+        ] {
+            dir.write(file, "");
+        }
+        let glob = |pattern: &str| glob(&dir.join(pattern));
+
+        // `[[]` and `[]]` are a literal bracket
+        assert_eq!(glob("[[]x].conf"), [dir.join("[x].conf")]);
+        assert_eq!(glob("x[]].conf"), [dir.join("x].conf")]);
+        // A backslash in a class is just a backslash
+        assert_eq!(glob("a[\\]b.conf"), [dir.join("a\\b.conf")]);
+        // `-` at the end of a class, and negation with `!`
+        assert_eq!(glob("a[-]*"), [dir.join("a-.conf")]);
+        assert_eq!(glob("[!ax].conf"), [dir.join("b.conf")]);
+        // An unclosed class matches nothing, and `**` skips hidden dirs
+        assert!(glob("[x.conf").is_empty());
+        assert!(!glob("**/*.conf").contains(&dir.join(".hidden/d.conf")));
+    }
+}
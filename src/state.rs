@@ -0,0 +1,200 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of search queries kept in the history.
+const MAX_HISTORY: usize = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Usage {
+    pub count: u32,
+    /// Seconds since the Unix epoch.
+    pub last_used: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct StateData {
+    /// Oldest first.
+    #[serde(default)]
+    search_history: Vec<String>,
+    /// Keyed by `Keybinding::id`.
+    #[serde(default)]
+    usage: HashMap<String, Usage>,
+    /// Keyed by `Keybinding::id`, in the order they were pinned.
+    #[serde(default)]
+    pinned: Vec<String>,
+}
+
+/// Data remembered between launches, stored in
+/// `$XDG_STATE_HOME/hypr-showkey/state.yaml`.
+#[derive(Debug, Default)]
+pub struct State {
+    path: Option<PathBuf>,
+    data: StateData,
+    dirty: bool,
+}
+
+impl State {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir().map(|dir| dir.join("hypr-showkey").join("state.yaml"))
+    }
+
+    /// Load the state from the default location. A missing or unreadable
+    /// state file is not fatal; we just start from scratch.
+    pub fn load() -> Self {
+        let Some(path) = Self::default_path() else {
+            return Self::default();
+        };
+
+        match Self::load_from(&path) {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("Warning: {:#}", e);
+                Self {
+                    path: Some(path),
+                    ..Self::default()
+                }
+            }
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let data = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read state file: {:?}", path))?;
+            serde_yaml::from_str(&content)
+                .with_context(|| format!("Failed to parse state file: {:?}", path))?
+        } else {
+            StateData::default()
+        };
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            data,
+            dirty: false,
+        })
+    }
+
+    /// Write the state back if anything changed. The file is written next
+    /// to the old one and renamed over it, so a crash can't leave it
+    /// truncated.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create state directory: {:?}", parent))?;
+        }
+        let content = serde_yaml::to_string(&self.data)?;
+        let temp = path.with_extension("yaml.tmp");
+        std::fs::write(&temp, content)
+            .with_context(|| format!("Failed to write state file: {:?}", temp))?;
+        std::fs::rename(&temp, path)
+            .with_context(|| format!("Failed to replace state file: {:?}", path))?;
+
+        Ok(())
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.data.search_history
+    }
+
+    /// Append a query to the history, moving it to the end if it was
+    /// already present.
+    pub fn push_history(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+
+        let history = &mut self.data.search_history;
+        history.retain(|q| q != query);
+        history.push(query.to_string());
+        if history.len() > MAX_HISTORY {
+            history.drain(..history.len() - MAX_HISTORY);
+        }
+        self.dirty = true;
+    }
+
+    pub fn record_use(&mut self, id: &str) {
+        let usage = self.data.usage.entry(id.to_string()).or_default();
+        usage.count += 1;
+        usage.last_used = now();
+        self.dirty = true;
+    }
+
+    pub fn usage(&self, id: &str) -> Option<&Usage> {
+        self.data.usage.get(id)
+    }
+
+    pub fn is_pinned(&self, id: &str) -> bool {
+        self.data.pinned.iter().any(|p| p == id)
+    }
+
+    /// Pin or unpin a binding. Returns whether it is pinned afterwards.
+    pub fn toggle_pin(&mut self, id: &str) -> bool {
+        self.dirty = true;
+        if self.is_pinned(id) {
+            self.data.pinned.retain(|p| p != id);
+            false
+        } else {
+            self.data.pinned.push(id.to_string());
+            true
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn test_push_history_dedupes_and_caps() {
+        let mut state = State::default();
+        for i in 0..MAX_HISTORY + 5 {
+            state.push_history(&format!("query {}", i));
+        }
+        state.push_history("query 10");
+        state.push_history("   ");
+
+        let history = state.history();
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history.last().map(String::as_str), Some("query 10"));
+        assert_eq!(history.iter().filter(|q| *q == "query 10").count(), 1);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new("state");
+        let path = dir.join("state.yaml");
+
+        let mut state = State::load_from(&path).unwrap();
+        state.push_history("kitty");
+        state.record_use("SUPER|Q||exec");
+        state.record_use("SUPER|Q||exec");
+        assert!(state.toggle_pin("SUPER|F||fullscreen"));
+        state.save().unwrap();
+
+        let loaded = State::load_from(&path).unwrap();
+        assert_eq!(loaded.history(), ["kitty"]);
+        assert_eq!(loaded.usage("SUPER|Q||exec").map(|u| u.count), Some(2));
+        assert!(loaded.is_pinned("SUPER|F||fullscreen"));
+        // Written through a temp file, which is renamed away
+        assert!(!path.with_extension("yaml.tmp").exists());
+    }
+}
@@ -0,0 +1,74 @@
+//! Fixtures shared by the unit tests.
+
+use std::path::{Path, PathBuf};
+
+/// A scratch directory in the system's temp dir, removed again when
+/// dropped. `name` keeps the directories of tests running in parallel apart.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("hypr-showkey-{}-{}", name, std::process::id()));
+        // Left over by an earlier run of the same process id
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.join(path)
+    }
+
+    /// Write `content` to `name` in the directory, creating the parent
+    /// directories `name` mentions.
+    pub fn write(&self, name: &str, content: &str) -> PathBuf {
+        let path = self.path.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// The files in `testdata/<dir>` with the given extension, sorted.
+pub fn testdata_files(dir: &str, extension: &str) -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata")
+        .join(dir);
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no .{} files in {:?}", extension, dir);
+    paths
+}
+
+/// Compare `actual` with the golden file at `path`. Run the tests with
+/// `UPDATE_GOLDEN=1` to rewrite the golden files instead.
+pub fn assert_golden(path: &Path, actual: &str) {
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(path).unwrap_or_default();
+    assert_eq!(
+        actual, expected,
+        "{:?} differs (UPDATE_GOLDEN=1 rewrites it)",
+        path
+    );
+}
@@ -1,17 +1,509 @@
-use ratatui::style::Color;
+use crate::config::{ElementStyle, ThemeSettings};
+use anyhow::{bail, Context, Result};
+use ratatui::style::{Color, Modifier, Style};
 
-pub fn parse_hex_color(hex: &str) -> Color {
-    let hex = hex.trim_start_matches('#');
+/// The 16 ANSI colors by name, in palette order.
+const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
 
-    if hex.len() != 6 {
-        return Color::White; // Fallback
+/// ratatui's names for the 16 ANSI colors, in palette order.
+const ANSI_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// The usual xterm values of the 16 ANSI colors, for finding the nearest.
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Parse a theme color: `#rgb`, `#rrggbb`, `#rrggbbaa`, Hyprland's
+/// `rgb(...)`, `rgba(...)` and `0xaarrggbb`, an ANSI name such as `red` or
+/// `bright-blue`, a 256-color index, or `default` for the terminal's own.
+/// Alpha is ignored.
+pub fn parse_color(value: &str) -> Result<Color> {
+    let value = value.trim();
+    let lower = value.to_lowercase().replace('_', "-");
+
+    if lower == "default" || lower == "reset" {
+        return Ok(Color::Reset);
+    }
+    if let Some(index) = ANSI_NAMES.iter().position(|name| {
+        *name == lower
+            || name.strip_prefix("bright-").map(|n| format!("light-{}", n)) == Some(lower.clone())
+    }) {
+        return Ok(ANSI_COLORS[index]);
+    }
+    if matches!(lower.as_str(), "gray" | "grey") {
+        return Ok(Color::DarkGray);
+    }
+    if let Ok(index) = lower.parse::<u8>() {
+        return Ok(Color::Indexed(index));
     }
+    if let Some(argb) = lower.strip_prefix("0x") {
+        if argb.len() == 8 && argb.chars().all(|c| c.is_ascii_hexdigit()) {
+            return parse_hex(&argb[2..]);
+        }
+        bail!("expected 0xaarrggbb");
+    }
+    if let Some(args) = lower
+        .strip_prefix("rgba(")
+        .or_else(|| lower.strip_prefix("rgb("))
+        .and_then(|v| v.strip_suffix(')'))
+    {
+        let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        return match parts[..] {
+            [hex] => parse_hex(hex),
+            [r, g, b] | [r, g, b, _] => {
+                let channel = |c: &str| {
+                    c.parse::<u8>()
+                        .with_context(|| format!("`{}` is not a number from 0 to 255", c))
+                };
+                Ok(Color::Rgb(channel(r)?, channel(g)?, channel(b)?))
+            }
+            _ => bail!("expected rgb(r, g, b), rgba(r, g, b, a) or rgb(rrggbb)"),
+        };
+    }
+    parse_hex(lower.strip_prefix('#').unwrap_or(&lower))
+}
 
-    let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(255);
-    let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(255);
-    let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(255);
+/// `rgb`, `rrggbb` or `rrggbbaa` hex digits.
+fn parse_hex(digits: &str) -> Result<Color> {
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!(
+            "expected #rrggbb, rgb(...), an ANSI color name, a number from 0 to 255 or `default`"
+        );
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&digits[i..i + len], 16).unwrap();
+    match digits.len() {
+        3 => Ok(Color::Rgb(
+            channel(0, 1) * 17,
+            channel(1, 1) * 17,
+            channel(2, 1) * 17,
+        )),
+        6 | 8 => Ok(Color::Rgb(channel(0, 2), channel(2, 2), channel(4, 2))),
+        n => bail!("expected 3, 6 or 8 hex digits, got {}", n),
+    }
+}
 
-    Color::Rgb(r, g, b)
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// No colors at all, e.g. with `NO_COLOR` or `--color never`.
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// The `--color` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorDepth {
+    /// Work out the color support from `--color` and the environment:
+    /// `NO_COLOR`, then `COLORTERM=truecolor`, then `TERM`.
+    pub fn detect(choice: ColorChoice, env: impl Fn(&str) -> Option<String>) -> Self {
+        match choice {
+            ColorChoice::Never => return ColorDepth::None,
+            ColorChoice::Always => return ColorDepth::TrueColor,
+            ColorChoice::Auto => {}
+        }
+        if env("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return ColorDepth::None;
+        }
+        if env("COLORTERM").is_some_and(|v| v == "truecolor" || v == "24bit") {
+            return ColorDepth::TrueColor;
+        }
+
+        let term = env("TERM").unwrap_or_default();
+        const TRUECOLOR_TERMS: [&str; 5] = [
+            "xterm-kitty",
+            "xterm-ghostty",
+            "alacritty",
+            "foot",
+            "wezterm",
+        ];
+        if TRUECOLOR_TERMS.iter().any(|t| term.starts_with(t)) || term.ends_with("-direct") {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else if term.is_empty() || term == "dumb" {
+            ColorDepth::None
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    /// The nearest color this terminal can show.
+    pub fn convert(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::None, _) => Color::Reset,
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256(r, g, b)),
+            (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => nearest_16(r, g, b),
+            (ColorDepth::Ansi16, Color::Indexed(index)) => {
+                let (r, g, b) = indexed_rgb(index);
+                nearest_16(r, g, b)
+            }
+            (_, color) => color,
+        }
+    }
+}
+
+/// The RGB value of a 256-color palette entry.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+    match index {
+        0..=15 => ANSI_RGB[index as usize],
+        16..=231 => {
+            let n = index - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// The closest entry of the 6x6x6 cube or the gray ramp.
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    (16..=255)
+        .min_by_key(|&index| distance(indexed_rgb(index), (r, g, b)))
+        .unwrap()
+}
+
+fn nearest_16(r: u8, g: u8, b: u8) -> Color {
+    let index = (0..16)
+        .min_by_key(|&index| distance(ANSI_RGB[index], (r, g, b)))
+        .unwrap();
+    ANSI_COLORS[index]
+}
+
+/// The SGR parameters that set `color` as the foreground (`layer` 38) or
+/// background (48), for output outside the TUI.
+pub fn sgr(color: Color, layer: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", layer, r, g, b),
+        Color::Indexed(index) => format!("{};5;{}", layer, index),
+        named => {
+            let index = ANSI_COLORS.iter().position(|c| *c == named)? as u8;
+            let base = if layer == 38 { 30 } else { 40 };
+            if index < 8 {
+                (base + index).to_string()
+            } else {
+                (base + 60 + index - 8).to_string()
+            }
+        }
+    };
+    Some(code)
+}
+
+/// A text modifier by name, as in `styles.key.modifiers`.
+pub fn parse_modifier(name: &str) -> Result<Modifier> {
+    Ok(match name.to_lowercase().replace('-', "_").as_str() {
+        "bold" => Modifier::BOLD,
+        "dim" => Modifier::DIM,
+        "italic" => Modifier::ITALIC,
+        "underline" | "underlined" => Modifier::UNDERLINED,
+        "blink" | "slow_blink" => Modifier::SLOW_BLINK,
+        "rapid_blink" => Modifier::RAPID_BLINK,
+        "reverse" | "reversed" => Modifier::REVERSED,
+        "hidden" => Modifier::HIDDEN,
+        "strikethrough" | "crossed_out" => Modifier::CROSSED_OUT,
+        _ => bail!("expected bold, dim, italic, underlined, blink, rapid_blink, reversed, hidden or crossed_out"),
+    })
+}
+
+/// The configured colors, parsed once, and the style of each part of the
+/// UI. Everything drawn goes through one of these styles so that a theme
+/// covers the whole screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// What the colors were reduced to; without colors the selection is
+    /// shown reversed.
+    pub depth: ColorDepth,
+    pub background: Color,
+    pub foreground: Color,
+    pub selected_bg: Color,
+    pub selected_fg: Color,
+    pub search_bg: Color,
+    pub search_fg: Color,
+    pub key: Color,
+    pub action: Color,
+    pub category: Color,
+    pub description: Color,
+    pub matched: Color,
+    pub border: Color,
+    /// In front of the selected row.
+    pub highlight_symbol: String,
+    /// Between a key and its description or action.
+    pub separator: String,
+    styles: ElementStyles,
+}
+
+/// The element styles with `theme.styles` applied.
+#[derive(Debug, Clone, PartialEq)]
+struct ElementStyles {
+    key: Style,
+    modifier: Style,
+    separator: Style,
+    description: Style,
+    action: Style,
+    category: Style,
+    matched: Style,
+    selected: Style,
+    flags: Style,
+}
+
+impl Theme {
+    /// Colors and styles are checked when the config is loaded; a color
+    /// that still doesn't parse uses the terminal's default.
+    pub fn new(settings: &ThemeSettings) -> Self {
+        Self::with_depth(settings, ColorDepth::TrueColor)
+    }
+
+    /// The theme reduced to what the terminal can show.
+    pub fn with_depth(settings: &ThemeSettings, depth: ColorDepth) -> Self {
+        let color = |value: &str| depth.convert(parse_color(value).unwrap_or(Color::Reset));
+        let colors = &settings.colors;
+        let key = color(&colors.key_color);
+        let description = color(&colors.description_color);
+        let selected = Style::default()
+            .fg(color(&colors.selected_fg))
+            .bg(color(&colors.selected_bg));
+        let selected = if depth == ColorDepth::None {
+            selected.add_modifier(Modifier::REVERSED)
+        } else {
+            selected
+        };
+
+        // Each element's default look, then what the config sets
+        let element = |default: Style, style: &Option<ElementStyle>| {
+            let Some(style) = style else {
+                return default;
+            };
+            let mut result = default;
+            if let Some(fg) = &style.fg {
+                result = result.fg(color(fg));
+            }
+            if let Some(bg) = &style.bg {
+                result = result.bg(color(bg));
+            }
+            if let Some(modifiers) = &style.modifiers {
+                let modifiers = modifiers
+                    .iter()
+                    .filter_map(|name| parse_modifier(name).ok())
+                    .fold(Modifier::empty(), |all, modifier| all | modifier);
+                result = result
+                    .remove_modifier(default.add_modifier)
+                    .add_modifier(modifiers);
+            }
+            result
+        };
+        let theme_styles = &settings.styles;
+        let key_style = Style::default().fg(key).add_modifier(Modifier::BOLD);
+        let styles = ElementStyles {
+            key: element(key_style, &theme_styles.key),
+            modifier: element(key_style, &theme_styles.modifier),
+            separator: element(Style::default(), &theme_styles.separator),
+            description: element(Style::default().fg(description), &theme_styles.description),
+            action: element(
+                Style::default().fg(color(&colors.action_color)),
+                &theme_styles.action,
+            ),
+            category: element(
+                Style::default()
+                    .fg(color(&colors.category_color))
+                    .add_modifier(Modifier::BOLD),
+                &theme_styles.category,
+            ),
+            matched: element(
+                Style::default().fg(color(&colors.matched_color)),
+                &theme_styles.matched,
+            ),
+            selected: element(selected, &theme_styles.selected),
+            flags: element(
+                Style::default().fg(description).add_modifier(Modifier::DIM),
+                &theme_styles.flags,
+            ),
+        };
+
+        Self {
+            depth,
+            background: color(&colors.background),
+            foreground: color(&colors.foreground),
+            selected_bg: color(&colors.selected_bg),
+            selected_fg: color(&colors.selected_fg),
+            search_bg: color(&colors.search_bg),
+            search_fg: color(&colors.search_fg),
+            key,
+            action: color(&colors.action_color),
+            category: color(&colors.category_color),
+            description,
+            matched: color(&colors.matched_color),
+            border: color(&colors.border_color),
+            highlight_symbol: settings
+                .symbols
+                .highlight
+                .clone()
+                .unwrap_or("> ".to_string()),
+            separator: settings
+                .symbols
+                .separator
+                .clone()
+                .unwrap_or(" → ".to_string()),
+            styles,
+        }
+    }
+
+    /// The whole screen, and any panel that isn't styled otherwise.
+    pub fn base(&self) -> Style {
+        Style::default().fg(self.foreground).bg(self.background)
+    }
+
+    /// Block borders and titles.
+    pub fn border(&self) -> Style {
+        Style::default().fg(self.border)
+    }
+
+    /// The search box with a query in it.
+    pub fn search(&self) -> Style {
+        Style::default().fg(self.search_fg).bg(self.search_bg)
+    }
+
+    /// The search box's placeholder text.
+    pub fn placeholder(&self) -> Style {
+        Style::default().fg(self.description).bg(self.search_bg)
+    }
+
+    /// Secondary text: the status bar, unselected tabs and hints.
+    pub fn muted(&self) -> Style {
+        Style::default().fg(self.description)
+    }
+
+    /// The selected tab and category headers.
+    pub fn category(&self) -> Style {
+        self.styles.category
+    }
+
+    /// The key of a key combination.
+    pub fn key(&self) -> Style {
+        self.styles.key
+    }
+
+    /// The modifiers of a key combination, with their ` + `.
+    pub fn modifier(&self) -> Style {
+        self.styles.modifier
+    }
+
+    /// The separator between a key and its description.
+    pub fn separator(&self) -> Style {
+        self.styles.separator
+    }
+
+    pub fn action(&self) -> Style {
+        self.styles.action
+    }
+
+    pub fn description(&self) -> Style {
+        self.styles.description
+    }
+
+    /// Pins and characters matching the search, drawn over the text's own
+    /// style.
+    pub fn matched(&self) -> Style {
+        self.styles.matched
+    }
+
+    /// Bind flags such as `[el]`.
+    pub fn flags(&self) -> Style {
+        self.styles.flags
+    }
+
+    /// Counts that need attention, like the diagnostics hint.
+    pub fn warning(&self) -> Style {
+        self.matched().add_modifier(Modifier::BOLD)
+    }
+
+    /// The selected row of a list.
+    pub fn selected(&self) -> Style {
+        self.styles.selected
+    }
+
+    /// Binds in a false `# hyprlang if` block.
+    pub fn inactive(&self) -> Style {
+        Style::default()
+            .fg(self.description)
+            .add_modifier(Modifier::DIM)
+    }
+
+    /// Popups drawn over the list, such as help and diagnostics.
+    pub fn popup(&self) -> Style {
+        self.base()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(&ThemeSettings::default())
+    }
 }
 
 #[cfg(test)]
@@ -19,9 +511,89 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_hex_color() {
-        assert_eq!(parse_hex_color("#ff0000"), Color::Rgb(255, 0, 0));
-        assert_eq!(parse_hex_color("00ff00"), Color::Rgb(0, 255, 0));
-        assert_eq!(parse_hex_color("#0000ff"), Color::Rgb(0, 0, 255));
+    fn test_parse_color() {
+        let parse = |value| parse_color(value).unwrap();
+        assert_eq!(parse("#ff0000"), Color::Rgb(255, 0, 0));
+        assert_eq!(parse("00ff00"), Color::Rgb(0, 255, 0));
+        assert_eq!(parse("#0000ff"), Color::Rgb(0, 0, 255));
+        assert_eq!(parse("#f80"), Color::Rgb(255, 136, 0));
+        assert_eq!(parse("#89b4facc"), Color::Rgb(137, 180, 250));
+        assert_eq!(parse("rgba(89b4faff)"), Color::Rgb(137, 180, 250));
+        assert_eq!(parse("rgb(137, 180, 250)"), Color::Rgb(137, 180, 250));
+        assert_eq!(parse("0xff89b4fa"), Color::Rgb(137, 180, 250));
+        assert_eq!(parse("bright-blue"), Color::LightBlue);
+        assert_eq!(parse("white"), Color::Gray);
+        assert_eq!(parse("208"), Color::Indexed(208));
+        assert_eq!(parse("default"), Color::Reset);
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("rgb(300, 0, 0)").is_err());
+        assert!(parse_color("blurple").is_err());
+        assert!(parse_color("0xaéééb").is_err());
+        assert!(parse_color("0xzz89b4fa").is_err());
+    }
+
+    #[test]
+    fn test_element_styles() {
+        let mut settings: ThemeSettings = serde_yaml::from_str(
+            "name: mocha\nstyles:\n  key: { fg: red, modifiers: [italic, underlined] }\n  \
+             selected: { bg: \"#45475a\" }\nsymbols:\n  highlight: \"▶ \"\n",
+        )
+        .unwrap();
+        settings.resolve(crate::appearance::known).unwrap();
+        let theme = Theme::new(&settings);
+        assert_eq!(theme.key().fg, Some(Color::Red));
+        assert_eq!(
+            theme.key().add_modifier,
+            Modifier::ITALIC | Modifier::UNDERLINED
+        );
+        assert!(theme.key().sub_modifier.contains(Modifier::BOLD));
+        // Parts that aren't set keep the theme's
+        assert_eq!(theme.modifier(), Theme::default().modifier());
+        assert_eq!(theme.selected().bg, Some(Color::Rgb(0x45, 0x47, 0x5a)));
+        assert_eq!(theme.selected().fg, Theme::default().selected().fg);
+        assert_eq!(theme.highlight_symbol, "▶ ");
+        assert_eq!(theme.separator, " → ");
+
+        let error =
+            serde_yaml::from_str::<ThemeSettings>("styles:\n  flags: { modifiers: [loud] }\n")
+                .unwrap_err()
+                .to_string();
+        assert!(
+            error.contains("styles.flags.modifiers: invalid modifier `loud`"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_color_depth() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let detect = |vars| ColorDepth::detect(ColorChoice::Auto, env(vars));
+        assert_eq!(
+            detect(&[("COLORTERM", "truecolor"), ("TERM", "xterm")]),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(detect(&[("TERM", "screen-256color")]), ColorDepth::Ansi256);
+        assert_eq!(detect(&[("TERM", "linux")]), ColorDepth::Ansi16);
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]),
+            ColorDepth::None
+        );
+        assert_eq!(
+            ColorDepth::detect(ColorChoice::Always, env(&[("NO_COLOR", "1")])),
+            ColorDepth::TrueColor
+        );
+
+        // Catppuccin Mocha's blue
+        let blue = Color::Rgb(137, 180, 250);
+        assert_eq!(ColorDepth::Ansi256.convert(blue), Color::Indexed(111));
+        assert_eq!(ColorDepth::Ansi16.convert(blue), Color::LightBlue);
+        assert_eq!(ColorDepth::None.convert(blue), Color::Reset);
     }
 }
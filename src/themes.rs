@@ -0,0 +1,563 @@
+use crate::config::{PartialThemeColors, ThemeColors, ThemeSettings, ThemeStyles, ThemeSymbols};
+use crate::theme::{parse_color, sgr, ColorDepth, Theme};
+use anyhow::{bail, Context, Result};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+
+/// The built-in themes, by full name.
+pub const PRESETS: [&str; 4] = [
+    "catppuccin_mocha",
+    "catppuccin_latte",
+    "catppuccin_macchiato",
+    "catppuccin_frappe",
+];
+
+/// Theme file extensions, in the order they are looked for.
+const EXTENSIONS: [&str; 3] = ["yaml", "yml", "toml"];
+
+/// A theme file in one of the theme directories, e.g.
+///
+/// ```yaml
+/// inherits: catppuccin_mocha
+/// colors:
+///   key_color: "#f5c2e7"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    /// A preset or another theme file to take the other colors from.
+    #[serde(default)]
+    inherits: Option<String>,
+    #[serde(default)]
+    colors: PartialThemeColors,
+    #[serde(default)]
+    styles: ThemeStyles,
+    #[serde(default)]
+    symbols: ThemeSymbols,
+}
+
+/// A theme that can be selected with `ui.theme`.
+pub struct ThemeEntry {
+    pub name: String,
+    /// `None` for the built-in presets.
+    pub path: Option<PathBuf>,
+}
+
+/// A loaded theme and the files it was read from, to watch for changes.
+pub struct LoadedTheme {
+    pub colors: ThemeColors,
+    pub styles: ThemeStyles,
+    pub symbols: ThemeSymbols,
+    pub files: Vec<PathBuf>,
+}
+
+impl LoadedTheme {
+    /// Just colors, with the default styles and symbols.
+    fn from_colors(colors: ThemeColors, files: Vec<PathBuf>) -> Self {
+        Self {
+            colors,
+            styles: ThemeStyles::default(),
+            symbols: ThemeSymbols::default(),
+            files,
+        }
+    }
+}
+
+/// Where theme files are looked for: the user's themes first, then the
+/// system-wide ones.
+pub fn theme_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dirs::config_dir()
+        .map(|dir| dir.join("hypr-showkey/themes"))
+        .into_iter()
+        .collect();
+    dirs.push(PathBuf::from("/usr/share/hypr-showkey/themes"));
+    dirs
+}
+
+/// A built-in theme by full or short name (`mocha` for `catppuccin_mocha`).
+pub fn preset(name: &str) -> Option<ThemeColors> {
+    match name.to_lowercase().as_str() {
+        "catppuccin_mocha" | "mocha" => Some(ThemeColors::catppuccin_mocha()),
+        "catppuccin_latte" | "latte" => Some(ThemeColors::catppuccin_latte()),
+        "catppuccin_macchiato" | "macchiato" => Some(ThemeColors::catppuccin_macchiato()),
+        "catppuccin_frappe" | "frappe" => Some(ThemeColors::catppuccin_frappe()),
+        _ => None,
+    }
+}
+
+/// Load a preset, a theme from the theme directories, or a theme file by
+/// path, following `inherits`.
+pub fn load(name: &str) -> Result<LoadedTheme> {
+    load_in(name, &theme_dirs(), &mut Vec::new())
+}
+
+fn load_in(name: &str, dirs: &[PathBuf], seen: &mut Vec<PathBuf>) -> Result<LoadedTheme> {
+    if let Some(colors) = preset(name) {
+        return Ok(LoadedTheme::from_colors(colors, seen.clone()));
+    }
+
+    let path = find(name, dirs).with_context(|| {
+        format!(
+            "Unknown theme `{}`; it is not a preset ({}) or a file in {}",
+            name,
+            PRESETS.join(", "),
+            dirs.iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" or ")
+        )
+    })?;
+    if seen.contains(&path) {
+        bail!("Theme {:?} inherits from itself", path);
+    }
+    seen.push(path.clone());
+
+    let file = read_theme_file(&path)?;
+    let mut theme = match &file.inherits {
+        Some(parent) => load_in(parent, dirs, seen)
+            .with_context(|| format!("Failed to load the theme {:?} inherits", path))?,
+        None => LoadedTheme::from_colors(ThemeColors::catppuccin_mocha(), seen.clone()),
+    };
+    file.colors
+        .validate("colors")
+        .and_then(|_| file.styles.validate("styles"))
+        .with_context(|| format!("Invalid theme file {:?}", path))?;
+    file.colors.apply(&mut theme.colors);
+    theme.styles.merge(file.styles);
+    theme.symbols.merge(file.symbols);
+    Ok(theme)
+}
+
+/// The file for a theme name, or the name itself when it is a path.
+fn find(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    if name.contains('/') || Path::new(name).extension().is_some() {
+        return resolve_path(name).ok().filter(|path| path.is_file());
+    }
+
+    dirs.iter()
+        .flat_map(|dir| {
+            EXTENSIONS
+                .iter()
+                .map(move |ext| dir.join(format!("{}.{}", name, ext)))
+        })
+        .find(|path| path.is_file())
+}
+
+/// A path from the config, with `~` and variables expanded; relative paths
+/// are relative to `~/.config/hypr-showkey/`.
+fn resolve_path(spec: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(crate::paths::expand(spec, |var| std::env::var(var).ok())?);
+    Ok(match dirs::config_dir() {
+        Some(config_dir) if path.is_relative() => config_dir.join("hypr-showkey").join(path),
+        _ => path,
+    })
+}
+
+fn read_theme_file(path: &Path) -> Result<ThemeFile> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read theme file {:?}", path))?;
+    if path.extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str(&content).with_context(|| format!("Invalid theme file {:?}", path))
+    } else {
+        serde_yaml::from_str(&content).with_context(|| format!("Invalid theme file {:?}", path))
+    }
+}
+
+/// Every theme that `ui.theme` can name: the presets, then the theme files
+/// (the first one of a name wins, as when loading).
+pub fn list() -> Vec<ThemeEntry> {
+    let mut themes: Vec<ThemeEntry> = PRESETS
+        .iter()
+        .map(|name| ThemeEntry {
+            name: name.to_string(),
+            path: None,
+        })
+        .collect();
+
+    for dir in theme_dirs() {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        paths.sort();
+        for path in paths {
+            let is_theme = path
+                .extension()
+                .is_some_and(|ext| EXTENSIONS.iter().any(|e| ext == *e));
+            let Some(name) = path.file_stem().map(|s| s.to_string_lossy().into_owned()) else {
+                continue;
+            };
+            if is_theme && !themes.iter().any(|theme| theme.name == name) {
+                themes.push(ThemeEntry {
+                    name,
+                    path: Some(path),
+                });
+            }
+        }
+    }
+
+    themes
+}
+
+/// Color scheme formats that can be imported with `theme: { FORMAT: path }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemeFormat {
+    /// A base16 or base24 scheme: `base00` to `base0F` (and `base10` to
+    /// `base17`), optionally under `palette:`.
+    Base16,
+    /// pywal's `colors.json`, also written by matugen and wallust.
+    Pywal,
+    /// A Hyprland file of `$name = rgba(...)` variables.
+    Hyprland,
+}
+
+impl SchemeFormat {
+    /// The format for a `theme:` key.
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "base16" | "base24" => Some(SchemeFormat::Base16),
+            "pywal" => Some(SchemeFormat::Pywal),
+            "hyprland" => Some(SchemeFormat::Hyprland),
+            _ => None,
+        }
+    }
+}
+
+/// Map a color scheme file onto the theme colors.
+pub fn import(format: SchemeFormat, spec: &str) -> Result<LoadedTheme> {
+    let path = resolve_path(spec)?;
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read color scheme {:?}", path))?;
+    let colors = match format {
+        SchemeFormat::Base16 => import_base16(&content),
+        SchemeFormat::Pywal => import_pywal(&content),
+        SchemeFormat::Hyprland => import_hyprland(&content),
+    }
+    .with_context(|| format!("Failed to import color scheme {:?}", path))?;
+
+    Ok(LoadedTheme::from_colors(colors, vec![path]))
+}
+
+/// Build the colors from a lookup of named palette entries; each color
+/// takes the first of its candidate names that is present.
+fn map_palette(
+    lookup: impl Fn(&str) -> Option<String>,
+    roles: [&[&str]; 12],
+) -> Result<ThemeColors> {
+    let mut colors = roles.iter().map(|names| {
+        names
+            .iter()
+            .find_map(|name| lookup(name))
+            .with_context(|| format!("no `{}` color", names.join("` or `")))
+    });
+    let mut next = || colors.next().unwrap();
+    Ok(ThemeColors {
+        background: next()?,
+        foreground: next()?,
+        selected_bg: next()?,
+        selected_fg: next()?,
+        search_bg: next()?,
+        search_fg: next()?,
+        key_color: next()?,
+        action_color: next()?,
+        category_color: next()?,
+        description_color: next()?,
+        matched_color: next()?,
+        border_color: next()?,
+    })
+}
+
+/// A `#rrggbb` color from a hex string with or without `#`.
+fn hex(value: &str) -> Option<String> {
+    let digits = value.trim().trim_start_matches('#');
+    (digits.len() >= 6 && digits.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| format!("#{}", digits[..6].to_lowercase()))
+}
+
+fn import_base16(content: &str) -> Result<ThemeColors> {
+    let value: Value = serde_yaml::from_str(content)?;
+    // The tinted-theming format nests the colors under `palette:`
+    let palette = value.get("palette").unwrap_or(&value);
+    let lookup = |name: &str| palette.get(name).and_then(Value::as_str).and_then(hex);
+
+    // Following the base16 styling guidelines
+    map_palette(
+        lookup,
+        [
+            &["base00"],
+            &["base05"],
+            &["base02"],
+            &["base05"],
+            &["base01"],
+            &["base05"],
+            &["base0D"],
+            &["base05"],
+            &["base0B"],
+            &["base04"],
+            &["base0A"],
+            &["base03"],
+        ],
+    )
+}
+
+fn import_pywal(content: &str) -> Result<ThemeColors> {
+    // JSON is YAML, so there's no need for a JSON parser
+    let value: Value = serde_yaml::from_str(content)?;
+    let lookup = |name: &str| {
+        ["special", "colors"]
+            .iter()
+            .find_map(|section| value.get(section)?.get(name)?.as_str())
+            .and_then(hex)
+    };
+
+    map_palette(
+        lookup,
+        [
+            &["background", "color0"],
+            &["foreground", "color15"],
+            &["color8"],
+            &["foreground", "color15"],
+            &["background", "color0"],
+            &["foreground", "color15"],
+            &["color4"],
+            &["foreground", "color15"],
+            &["color2"],
+            &["color7"],
+            &["color3"],
+            &["color8"],
+        ],
+    )
+}
+
+fn import_hyprland(content: &str) -> Result<ThemeColors> {
+    let (document, errors) = crate::hyprlang::parse(content);
+    if let Some(error) = errors.first() {
+        bail!("line {}: {}", error.line, error.message);
+    }
+    let variables: Vec<(String, String)> = document
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            crate::hyprlang::Node::Variable(entry) => Some((
+                entry.key.trim_start_matches('$').to_lowercase(),
+                entry.value(),
+            )),
+            _ => None,
+        })
+        .collect();
+    let lookup = |name: &str| {
+        variables
+            .iter()
+            .rev()
+            .find(|(variable, _)| variable == name)
+            .and_then(|(_, value)| hyprland_color(value))
+    };
+
+    // Catppuccin's Hyprland palettes, then wallust/pywal style names
+    map_palette(
+        lookup,
+        [
+            &["background", "base", "color0"],
+            &["foreground", "text", "color15"],
+            &["surface0", "color8"],
+            &["foreground", "text", "color15"],
+            &["background", "mantle", "base", "color0"],
+            &["foreground", "text", "color15"],
+            &["blue", "color4"],
+            &["foreground", "text", "color15"],
+            &["green", "color2"],
+            &["subtext1", "subtext0", "color7"],
+            &["yellow", "color3"],
+            &["surface2", "overlay0", "color8"],
+        ],
+    )
+}
+
+/// A `#rrggbb` color from Hyprland's color syntax: `rgb(rrggbb)`,
+/// `rgba(rrggbbaa)`, `rgb(r, g, b)`, `rgba(r, g, b, a)` or `0xaarrggbb`.
+/// Other variables, such as `$gaps = 5`, are not colors.
+fn hyprland_color(value: &str) -> Option<String> {
+    match parse_color(value).ok()? {
+        Color::Rgb(r, g, b) => Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        _ => None,
+    }
+}
+
+/// A sample keybinding in the theme's styles, reduced to `depth`, with
+/// ANSI escapes, for `hypr-showkey themes`.
+pub fn preview(settings: &ThemeSettings, depth: ColorDepth) -> String {
+    let theme = Theme::with_depth(settings, depth);
+    let paint = |style: Style, text: &str| {
+        let mut codes = Vec::new();
+        for (layer, color) in [(38, style.fg), (48, style.bg.or(Some(theme.background)))] {
+            codes.extend(color.and_then(|color| sgr(color, layer)));
+        }
+        for (modifier, code) in [
+            (Modifier::BOLD, "1"),
+            (Modifier::DIM, "2"),
+            (Modifier::ITALIC, "3"),
+            (Modifier::UNDERLINED, "4"),
+            (Modifier::REVERSED, "7"),
+            (Modifier::CROSSED_OUT, "9"),
+        ] {
+            if style.add_modifier.contains(modifier) {
+                codes.push(code.to_string());
+            }
+        }
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    };
+
+    [
+        paint(theme.border(), "│"),
+        paint(theme.matched(), " ★ "),
+        paint(theme.modifier(), "SUPER + "),
+        paint(theme.key(), "Q"),
+        paint(theme.separator(), &theme.separator),
+        paint(theme.description(), "Kill active window "),
+        paint(theme.selected(), " selected "),
+        paint(theme.category(), " Windows "),
+        paint(theme.search(), " search "),
+        paint(theme.border(), "│"),
+    ]
+    .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn test_theme_files_inherit() {
+        let dir = TempDir::new("themes");
+        dir.write(
+            "pink.toml",
+            "inherits = \"latte\" # a preset\n\n[colors]\nkey_color = \"#f5c2e7\"\n\n\
+             [styles]\nkey = { modifiers = [\n  \"bold\",\n  \"italic\", # trailing comma\n] }\n\n\
+             [symbols]\nseparator = \" \\u2192 \"\nhighlight = '> '\n",
+        );
+        dir.write(
+            "pinker.yaml",
+            "inherits: pink\ncolors:\n  border_color: \"#ff00ff\"\n",
+        );
+        dir.write("loop.yaml", "inherits: loop\n");
+        let dirs = [dir.path().to_path_buf()];
+
+        let theme = load_in("pinker", &dirs, &mut Vec::new()).unwrap();
+        assert_eq!(theme.colors.key_color, "#f5c2e7");
+        assert_eq!(theme.colors.border_color, "#ff00ff");
+        assert_eq!(
+            theme.styles.key.unwrap().modifiers.unwrap(),
+            ["bold", "italic"]
+        );
+        assert_eq!(
+            theme.colors.background,
+            ThemeColors::catppuccin_latte().background
+        );
+        assert_eq!(theme.symbols.separator.as_deref(), Some(" → "));
+        assert_eq!(theme.symbols.highlight.as_deref(), Some("> "));
+        assert_eq!(
+            theme.files,
+            [dir.join("pinker.yaml"), dir.join("pink.toml")]
+        );
+
+        let error = load_in("loop", &dirs, &mut Vec::new()).err().unwrap();
+        assert!(format!("{:#}", error).contains("inherits from itself"));
+        assert!(load_in("missing", &dirs, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_import_color_schemes() {
+        let base16 = "scheme: Test\npalette:\n".to_string()
+            + &(0..16)
+                .map(|i| format!("  base0{:X}: \"{:02x}{:02x}{:02x}\"\n", i, i, i, i))
+                .collect::<String>();
+        let colors = import_base16(&base16).unwrap();
+        assert_eq!(colors.background, "#000000");
+        assert_eq!(colors.key_color, "#0d0d0d");
+        assert!(
+            format!("{:#}", import_base16("base00: \"000000\"").unwrap_err())
+                .contains("no `base05` color")
+        );
+
+        let pywal = r##"{"special": {"background": "#101010", "foreground": "#f0f0f0"},
+            "colors": {"color2": "#00ff00", "color3": "#ffff00", "color4": "#0000ff",
+                       "color7": "#c0c0c0", "color8": "#808080"}}"##;
+        let colors = import_pywal(pywal).unwrap();
+        assert_eq!(colors.background, "#101010");
+        assert_eq!(colors.key_color, "#0000ff");
+
+        let hyprland = "$base = rgb(1e1e2e)\n$text = rgba(cdd6f4ff)\n$surface0 = 0xff313244\n\
+                        $blue = rgb(137, 180, 250)\n$green = rgb(a6e3a1)\n$subtext1 = rgb(bac2de)\n\
+                        $yellow = rgb(f9e2af)\n$surface2 = rgb(585b70)\n";
+        let colors = import_hyprland(hyprland).unwrap();
+        assert_eq!(colors.foreground, "#cdd6f4");
+        assert_eq!(colors.selected_bg, "#313244");
+        assert_eq!(colors.key_color, "#89b4fa");
+    }
+
+    #[test]
+    fn test_imports_fall_back_to_other_names() {
+        // base24 schemes are flat and add base10 to base17, which are unused
+        let base24: String = (0..24)
+            .map(|i| format!("base{:02X}: \"#{:02X}{:02X}{:02X}\"\n", i, i, i, i))
+            .collect();
+        let colors = import_base16(&base24).unwrap();
+        assert_eq!(colors.background, "#000000");
+        assert_eq!(colors.foreground, "#050505");
+        assert_eq!(colors.border_color, "#030303");
+
+        // No `special` section: the terminal colors stand in
+        let pywal = r##"{"colors": {"color0": "#000000", "color2": "#00ff00",
+            "color3": "#ffff00", "color4": "#0000ff", "color7": "#c0c0c0",
+            "color8": "#808080", "color15": "#ffffff"}}"##;
+        let colors = import_pywal(pywal).unwrap();
+        assert_eq!(colors.background, "#000000");
+        assert_eq!(colors.foreground, "#ffffff");
+        assert_eq!(colors.selected_bg, "#808080");
+        let error = import_pywal(r##"{"colors": {"color0": "#000000"}}"##).unwrap_err();
+        assert!(format!("{:#}", error).contains("no `foreground` or `color15` color"));
+
+        // wallust-style names; the last definition wins and other
+        // variables are skipped
+        let hyprland: String = (0..16)
+            .map(|i| format!("$color{} = rgb({:02x}{:02x}{:02x})\n", i, i, i, i))
+            .collect::<String>()
+            + "$gaps = 5\n$color4 = rgba(0000ffcc)\n";
+        let colors = import_hyprland(&hyprland).unwrap();
+        assert_eq!(colors.background, "#000000");
+        assert_eq!(colors.foreground, "#0f0f0f");
+        assert_eq!(colors.key_color, "#0000ff");
+        let error = import_hyprland("$base = rgb(000000\n}\n").unwrap_err();
+        assert!(error.to_string().starts_with("line 2:"), "{}", error);
+    }
+
+    #[test]
+    fn test_imported_scheme_is_selected_from_config() {
+        let dir = TempDir::new("scheme");
+        let scheme = dir.write(
+            "colors.conf",
+            "$background = rgb(101010)\n$foreground = rgb(f0f0f0)\n$color2 = rgb(00ff00)\n\
+             $color3 = rgb(ffff00)\n$color4 = rgb(0000ff)\n$color7 = rgb(c0c0c0)\n\
+             $color8 = rgb(808080)\n",
+        );
+        let mut theme: ThemeSettings = serde_yaml::from_str(&format!(
+            "hyprland: {:?}\ncolors:\n  key_color: \"#ff0000\"\n",
+            scheme
+        ))
+        .unwrap();
+        assert_eq!(theme.resolve(|| unreachable!()).unwrap(), None);
+        assert_eq!(theme.name, scheme.to_string_lossy());
+        assert_eq!(theme.colors.background, "#101010");
+        assert_eq!(theme.colors.key_color, "#ff0000");
+        assert_eq!(theme.overrides, ["key_color"]);
+        // Watched, so editing the scheme re-themes on hot reload
+        assert_eq!(theme.files, [scheme]);
+
+        let mut theme: ThemeSettings =
+            serde_yaml::from_str(&format!("base16: {:?}", dir.join("missing.yaml"))).unwrap();
+        let error = theme.resolve(|| unreachable!()).unwrap_err();
+        assert!(format!("{:#}", error).contains("Failed to read color scheme"));
+    }
+}
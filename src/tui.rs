@@ -1,57 +1,236 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
+    style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Cell, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph, Row,
+        Table, TableState, Tabs, Wrap,
+    },
     Frame, Terminal,
 };
-use std::{collections::HashMap, io};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::HashMap,
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// Two clicks on the same item within this interval count as a double-click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// How long to wait for input before checking the watched files again.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Editors often save in several steps; wait this long after the first
+/// change so that we reload once, with the final contents.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(100);
+
+use crate::{
+    appearance,
+    config::{Config, EnterAction, InputStyle, ListLayout, SortMode, TableColumn},
+    diagnostics::{count_summary, Diagnostic, Location},
+    free::find_free_keys,
+    input::LineEditor,
+    keyboard::{KeyboardLayout, KeyboardView},
+    keymap::{Action, KeyChord},
+    parser::{HyprlandParser, Keybinding},
+    state::State,
+    theme::{ColorDepth, Theme},
+    watcher::FileWatcher,
+};
+
+/// Whether typed characters go into the search query or are treated as commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputMode {
+    Normal,
+    Insert,
+}
 
-use crate::{config::Config, parser::Keybinding, theme::parse_hex_color};
+/// Which main panel is shown below the tabs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    List,
+    Keyboard,
+}
+
+/// Restricts the list to one key chord, set by picking a key in the
+/// keyboard view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyFilter {
+    modifiers: Vec<String>,
+    key: String,
+}
+
+/// Which subset of the keybindings the list shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tab {
+    All,
+    Pinned,
+    Category(String),
+}
+
+impl Tab {
+    fn title(&self) -> String {
+        match self {
+            Tab::All => "All".to_string(),
+            Tab::Pinned => "★ Pinned".to_string(),
+            Tab::Category(name) => name.clone(),
+        }
+    }
+}
+
+/// Screen areas from the last draw, used to map mouse clicks back to
+/// tabs and list items.
+#[derive(Debug, Default)]
+struct ClickAreas {
+    tabs: Vec<Rect>,
+    help: Rect,
+    diagnostics: Rect,
+    columns: Vec<ColumnArea>,
+}
+
+/// What a previous click landed on, for detecting double-clicks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClickTarget {
+    Item(usize),
+    Key(usize, usize),
+}
+
+#[derive(Debug)]
+struct ColumnArea {
+    area: Rect,
+    /// Index into `filtered_keybindings` of the column's first item.
+    start: usize,
+    /// Rendered height of each item in the column.
+    heights: Vec<u16>,
+}
+
+/// The files to reload the keybindings from when they change.
+struct HotReload {
+    /// `None` when running on the defaults without a `showkey.yaml`.
+    config_file: Option<PathBuf>,
+    watcher: FileWatcher,
+    /// `--define` variables, applied again on every reload.
+    defines: HashMap<String, String>,
+    /// When a watched file last changed, while waiting for the writes to
+    /// settle.
+    changed_at: Option<Instant>,
+}
+
+/// Something to do with the chosen keybinding once the terminal is restored.
+enum ExitAction {
+    Print(Keybinding),
+    Execute(Keybinding),
+}
 
 pub struct App {
     keybindings: Vec<Keybinding>,
     filtered_keybindings: Vec<(usize, Keybinding)>, // (original_index, keybinding)
     categories: HashMap<String, Vec<usize>>,        // category -> indices into keybindings
-    search_query: String,
+    tabs: Vec<Tab>,
+    current_tab: usize,
+    view: View,
+    keyboard: KeyboardView,
+    key_filter: Option<KeyFilter>,
+    search: LineEditor,
+    input_mode: InputMode,
+    history_index: Option<usize>, // position while recalling search history
+    history_draft: String,        // query typed before history recall started
+    sort_mode: SortMode,
+    layout: ListLayout,
     list_state: ListState,
+    table_state: TableState,
+    /// The table column picked for resizing, as an index into
+    /// `config.ui.table.columns`.
+    table_column: Option<usize>,
+    /// `ui.table.widths`, plus the columns resized since.
+    table_widths: HashMap<TableColumn, u16>,
+    /// The width each table column got when last drawn.
+    drawn_widths: Vec<u16>,
     show_help: bool,
+    popup: Option<(String, Vec<String>)>, // (title, lines)
+    popup_scroll: u16,
+    diagnostics: Vec<Diagnostic>,
+    show_inactive: bool,
+    status_message: Option<String>,
+    exit_action: Option<ExitAction>,
+    /// A file to open in the editor once the key press is handled.
+    edit_request: Option<Location>,
     config: Config,
+    /// Built from `config.ui.theme` whenever the config is (re)loaded.
+    theme: Theme,
+    state: State,
     matcher: SkimMatcherV2,
     columns: usize,               // Number of columns to display
     column_lists: Vec<ListState>, // List states for each column
+    click_areas: ClickAreas,
+    last_click: Option<(Instant, ClickTarget)>,
+    hot_reload: Option<HotReload>,
+    mouse_captured: bool, // `ui.mouse` when the TUI started
 }
 
 impl App {
-    pub fn new(keybindings: Vec<Keybinding>, config: &Config) -> Self {
+    pub fn new(keybindings: Vec<Keybinding>, config: &Config, state: State) -> Self {
+        let layout = KeyboardLayout::load(&config.ui.keyboard_layout).unwrap_or_else(|e| {
+            eprintln!("Warning: {:#}, falling back to the ANSI layout", e);
+            KeyboardLayout::ansi()
+        });
+
         let mut app = Self {
-            keybindings: keybindings.clone(),
-            filtered_keybindings: keybindings
-                .iter()
-                .enumerate()
-                .map(|(i, kb)| (i, kb.clone()))
-                .collect(),
+            keybindings,
+            filtered_keybindings: Vec::new(),
             categories: HashMap::new(),
-            search_query: String::new(),
+            tabs: Vec::new(),
+            current_tab: 0,
+            view: View::List,
+            keyboard: KeyboardView::new(layout),
+            key_filter: None,
+            search: LineEditor::new(),
+            input_mode: InputMode::Insert,
+            history_index: None,
+            history_draft: String::new(),
+            sort_mode: config.ui.sort,
+            layout: config.ui.layout,
             list_state: ListState::default(),
+            table_state: TableState::default(),
+            table_column: None,
+            table_widths: config.ui.table.widths.clone(),
+            drawn_widths: Vec::new(),
             show_help: false,
+            popup: None,
+            popup_scroll: 0,
+            diagnostics: Vec::new(),
+            show_inactive: config.ui.show_inactive,
+            status_message: None,
+            exit_action: None,
+            edit_request: None,
             config: config.clone(),
+            theme: Theme::new(&config.ui.theme),
+            state,
             matcher: SkimMatcherV2::default(),
             columns: 1,
             column_lists: vec![ListState::default()],
+            click_areas: ClickAreas::default(),
+            last_click: None,
+            hot_reload: None,
+            mouse_captured: false,
         };
 
         app.build_categories();
-        app.list_state.select(Some(0));
-        app.column_lists[0].select(Some(0));
+        app.filter_keybindings();
         app
     }
 
@@ -64,28 +243,376 @@ impl App {
                 .or_default()
                 .push(index);
         }
+
+        let mut names: Vec<&String> = self.categories.keys().collect();
+        names.sort();
+        self.tabs = [Tab::All, Tab::Pinned]
+            .into_iter()
+            .chain(names.into_iter().map(|name| Tab::Category(name.clone())))
+            .collect();
+        self.current_tab = self.current_tab.min(self.tabs.len() - 1);
+    }
+
+    /// Reload everything when `config_file`, one of the Hyprland `files` or
+    /// a theme file changes while the TUI is open.
+    pub fn watch(
+        &mut self,
+        config_file: Option<PathBuf>,
+        files: &[PathBuf],
+        defines: HashMap<String, String>,
+    ) {
+        let mut paths = files.to_vec();
+        paths.extend(config_file.clone());
+        paths.extend(self.config.ui.theme.files.iter().cloned());
+        match FileWatcher::new(&paths) {
+            Ok(watcher) => {
+                self.hot_reload = Some(HotReload {
+                    config_file,
+                    watcher,
+                    defines,
+                    changed_at: None,
+                })
+            }
+            Err(e) => eprintln!("Warning: {:#}, hot reload is disabled", e),
+        }
+    }
+
+    /// Whether a watched file changed since the last check.
+    fn files_changed(&mut self) -> bool {
+        let Some(hot_reload) = &mut self.hot_reload else {
+            return false;
+        };
+        // Editors write in several steps; wait until they stop for a while
+        let now = Instant::now();
+        if hot_reload.watcher.changed() {
+            hot_reload.changed_at = Some(now);
+        }
+        match hot_reload.changed_at {
+            Some(changed_at) if now.duration_since(changed_at) >= RELOAD_DEBOUNCE => {
+                hot_reload.changed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Re-read the configuration and the Hyprland files. On failure the
+    /// current keybindings are kept and the error is shown in the status bar.
+    fn reload(&mut self) {
+        let Some(hot_reload) = &mut self.hot_reload else {
+            return;
+        };
+
+        let result =
+            Config::load(hot_reload.config_file.as_deref(), appearance::known).and_then(|config| {
+                let output = HyprlandParser::new(&config)
+                    .with_defines(hot_reload.defines.clone())
+                    .parse()?;
+                Ok((config, output))
+            });
+
+        match result {
+            Ok((config, output)) => {
+                // A `source` line may have been added or removed
+                let mut paths = output.files.clone();
+                paths.extend(hot_reload.config_file.clone());
+                paths.extend(config.ui.theme.files.iter().cloned());
+                if let Err(e) = hot_reload.watcher.set_paths(&paths) {
+                    self.status_message = Some(format!("Watch failed: {:#}", e));
+                }
+
+                let count = output.keybindings.len();
+                let problems = count_summary(&output.diagnostics)
+                    .map(|summary| {
+                        let key = self.key_hint(Action::Diagnostics, false);
+                        format!(" ({}, {} to show)", summary, key)
+                    })
+                    .unwrap_or_default();
+                // Warnings go to the status line; stderr would draw over the TUI
+                let warnings: Vec<String> = config
+                    .warnings
+                    .iter()
+                    .chain(&output.warnings)
+                    .cloned()
+                    .collect();
+                self.diagnostics = output.diagnostics;
+                self.apply_reload(config, output.keybindings);
+                self.status_message
+                    .get_or_insert_with(|| match warnings.as_slice() {
+                        [] => format!("Reloaded {} keybindings{}", count, problems),
+                        [warning] => format!("Reloaded with a warning: {}", warning),
+                        [warning, rest @ ..] => {
+                            format!("Reloaded with {} warnings: {}", rest.len() + 1, warning)
+                        }
+                    });
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Reload failed: {:#}", e));
+            }
+        }
+    }
+
+    /// Swap in freshly parsed keybindings, keeping the search query, tab and
+    /// selected binding (matched by `Keybinding::id`).
+    fn apply_reload(&mut self, config: Config, keybindings: Vec<Keybinding>) {
+        let selected_id = self
+            .list_state
+            .selected()
+            .and_then(|i| self.filtered_keybindings.get(i))
+            .map(|(_, kb)| kb.id());
+        let tab = self.tabs[self.current_tab].clone();
+
+        if config.ui.keyboard_layout != self.config.ui.keyboard_layout {
+            match KeyboardLayout::load(&config.ui.keyboard_layout) {
+                Ok(layout) => self.keyboard = KeyboardView::new(layout),
+                Err(e) => self.status_message = Some(format!("Warning: {:#}", e)),
+            }
+        }
+
+        if config.ui.table != self.config.ui.table {
+            self.table_widths = config.ui.table.widths.clone();
+            self.table_column = None;
+        }
+
+        self.theme = Theme::with_depth(&config.ui.theme, self.theme.depth);
+        self.config = config;
+        self.keybindings = keybindings;
+        self.build_categories();
+        self.current_tab = self.tabs.iter().position(|t| *t == tab).unwrap_or(0);
+        self.filter_keybindings();
+
+        if let Some(position) = selected_id.and_then(|id| {
+            self.filtered_keybindings
+                .iter()
+                .position(|(_, kb)| kb.id() == id)
+        }) {
+            self.list_state.select(Some(position));
+            self.update_column_selection();
+        }
+    }
+
+    /// Reduce the theme to the colors the terminal supports.
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.theme = Theme::with_depth(&self.config.ui.theme, depth);
+    }
+
+    /// Start on the "Pinned" tab instead of "All".
+    pub fn show_pinned(&mut self) {
+        self.select_tab(1);
+    }
+
+    fn select_tab(&mut self, tab: usize) {
+        self.current_tab = tab % self.tabs.len();
+        self.view = View::List;
+        self.filter_keybindings();
+    }
+
+    fn toggle_keyboard_view(&mut self) {
+        self.view = match self.view {
+            View::List => View::Keyboard,
+            View::Keyboard => View::List,
+        };
+        self.last_click = None;
+    }
+
+    /// Show the bindings for the key under the keyboard view's cursor in
+    /// the list.
+    fn jump_to_selected_key(&mut self) {
+        let Some(key) = self.keyboard.selected_key() else {
+            return;
+        };
+        self.key_filter = Some(KeyFilter {
+            modifiers: self.keyboard.modifiers.clone(),
+            key: crate::parser::normalize_key(&key.key),
+        });
+        self.view = View::List;
+        self.current_tab = 0;
+        self.search.clear();
+        self.input_mode = InputMode::Normal;
+        self.filter_keybindings();
+    }
+
+    /// Clear the keyboard view's key filter. Returns `false` if none was set.
+    fn clear_key_filter(&mut self) -> bool {
+        if self.key_filter.take().is_none() {
+            return false;
+        }
+        self.filter_keybindings();
+        true
+    }
+
+    /// Open a popup listing the keys with no binding under `modifiers`.
+    fn show_free_keys(&mut self, modifiers: &[String], submap: Option<&str>) {
+        let result = find_free_keys(&self.keybindings, modifiers, submap);
+        let chord = if modifiers.is_empty() {
+            "no modifiers".to_string()
+        } else {
+            modifiers.join(" + ")
+        };
+        let title = match submap {
+            Some(submap) => format!("Free keys: {} in submap {}", chord, submap),
+            None => format!("Free keys: {} ({} free)", chord, result.free_count()),
+        };
+        self.open_popup(title, result.report());
+    }
+
+    fn open_popup(&mut self, title: String, lines: Vec<String>) {
+        self.popup = Some((title, lines));
+        self.popup_scroll = 0;
+    }
+
+    /// Problems found while parsing, shown in the diagnostics panel.
+    pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics = diagnostics;
+    }
+
+    /// Open a popup listing the parse diagnostics.
+    fn show_diagnostics(&mut self) {
+        let Some(summary) = count_summary(&self.diagnostics) else {
+            self.status_message = Some("No problems found".to_string());
+            return;
+        };
+        let lines = self
+            .diagnostics
+            .iter()
+            .flat_map(|d| {
+                let mut lines = vec![d.summary(), format!("    {}", d.source_line.trim())];
+                if let Some(note) = &d.note {
+                    lines.push(format!("    note: {}", note));
+                }
+                lines.push(String::new());
+                lines
+            })
+            .collect();
+        self.open_popup(format!("Diagnostics: {}", summary), lines);
+    }
+
+    /// Free keys for the selected binding's modifier set and submap.
+    fn show_free_keys_for_selected(&mut self) {
+        let (modifiers, submap) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.filtered_keybindings.get(i))
+            .map(|(_, kb)| (kb.modifiers(), kb.submap.clone()))
+            .unwrap_or_else(|| (vec!["SUPER".to_string()], None));
+        self.show_free_keys(&modifiers, submap.as_deref());
+    }
+
+    fn next_tab(&mut self) {
+        self.select_tab(self.current_tab + 1);
+    }
+
+    fn previous_tab(&mut self) {
+        self.select_tab(self.current_tab + self.tabs.len() - 1);
+    }
+
+    fn in_current_tab(&self, index: usize, kb: &Keybinding) -> bool {
+        if kb.hidden || (kb.inactive && !self.show_inactive) {
+            return false;
+        }
+
+        if let Some(filter) = &self.key_filter {
+            if kb.modifiers() != filter.modifiers || kb.key_name() != filter.key {
+                return false;
+            }
+        }
+
+        match &self.tabs[self.current_tab] {
+            Tab::All => true,
+            Tab::Pinned => self.state.is_pinned(&kb.id()),
+            Tab::Category(name) => self
+                .categories
+                .get(name)
+                .is_some_and(|indices| indices.contains(&index)),
+        }
+    }
+
+    /// Show or hide the binds in false `# hyprlang if` blocks.
+    fn toggle_inactive(&mut self) {
+        self.show_inactive = !self.show_inactive;
+        let count = self.keybindings.iter().filter(|kb| kb.inactive).count();
+        self.status_message = Some(format!(
+            "{} {} inactive keybindings",
+            if self.show_inactive {
+                "Showing"
+            } else {
+                "Hiding"
+            },
+            count
+        ));
+        self.filter_keybindings();
+    }
+
+    /// Pin or unpin the selected keybinding, keeping it selected.
+    fn toggle_pin(&mut self) {
+        let Some(&(index, ref kb)) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.filtered_keybindings.get(i))
+        else {
+            return;
+        };
+
+        let pinned = self.state.toggle_pin(&kb.id());
+        self.status_message = Some(format!(
+            "{}: {}",
+            if pinned { "Pinned" } else { "Unpinned" },
+            kb.key
+        ));
+        self.filter_keybindings();
+        if let Some(position) = self
+            .filtered_keybindings
+            .iter()
+            .position(|(i, _)| *i == index)
+        {
+            self.list_state.select(Some(position));
+            self.update_column_selection();
+        }
     }
 
     pub fn run(&mut self) -> Result<()> {
         // Setup terminal
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+        self.mouse_captured = self.config.ui.mouse;
+        if self.mouse_captured {
+            execute!(stdout, EnableMouseCapture)?;
+        }
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
         // Main loop
-        let result = self.run_app(&mut terminal);
+        let mut result = self.run_app(&mut terminal);
 
         // Restore terminal
         disable_raw_mode()?;
+        if self.mouse_captured {
+            execute!(terminal.backend_mut(), DisableMouseCapture)?;
+        }
         execute!(
             terminal.backend_mut(),
             LeaveAlternateScreen,
-            DisableMouseCapture
+            DisableBracketedPaste
         )?;
         terminal.show_cursor()?;
 
+        self.state.push_history(self.search.value());
+        if let Err(e) = self.state.save() {
+            eprintln!("Warning: {:#}", e);
+        }
+
+        if result.is_ok() {
+            result = match self.exit_action.take() {
+                Some(ExitAction::Print(kb)) => {
+                    println!("{}\t{}\t{}", kb.key, kb.action, kb.description);
+                    Ok(())
+                }
+                Some(ExitAction::Execute(kb)) => dispatch(&kb),
+                None => Ok(()),
+            };
+        }
+
         result
     }
 
@@ -93,96 +620,643 @@ impl App {
         loop {
             terminal.draw(|f| self.ui(f))?;
 
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc if !self.show_help => break,
-                        KeyCode::Char('?') | KeyCode::F(1) => {
-                            self.show_help = !self.show_help;
-                        }
-                        KeyCode::Esc if self.show_help => {
-                            self.show_help = false;
-                        }
-                        KeyCode::Down | KeyCode::Char('j') if !self.show_help => {
-                            self.next();
-                        }
-                        KeyCode::Up | KeyCode::Char('k') if !self.show_help => {
-                            self.previous();
-                        }
-                        KeyCode::Char(c) if !self.show_help => {
-                            self.search_query.push(c);
-                            self.filter_keybindings();
-                        }
-                        KeyCode::Backspace if !self.show_help => {
-                            self.search_query.pop();
-                            self.filter_keybindings();
-                        }
-                        KeyCode::Enter if !self.show_help => {
-                            // Copy selected keybinding to clipboard or show details
-                            if let Some(selected) = self.list_state.selected() {
-                                if let Some((_, _keybinding)) =
-                                    self.filtered_keybindings.get(selected)
-                                {
-                                    // For now, just continue - could implement clipboard copying here
-                                    continue;
-                                }
-                            }
-                        }
-                        _ => {}
+            if event::poll(POLL_INTERVAL)? {
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press && self.handle_key(key) => {
+                        break
                     }
+                    Event::Paste(text) => self.handle_paste(&text),
+                    Event::Mouse(mouse) if self.handle_mouse(mouse) => break,
+                    _ => {}
                 }
             }
+
+            if let Some(location) = self.edit_request.take() {
+                self.open_editor(terminal, &location)?;
+            }
+
+            if self.files_changed() {
+                self.reload();
+            }
         }
         Ok(())
     }
 
+    /// Suspend the TUI and open `location` in `$VISUAL` or `$EDITOR`. The
+    /// watcher reloads the file once it is saved.
+    fn open_editor<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        location: &Location,
+    ) -> Result<()> {
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+
+        disable_raw_mode()?;
+        execute!(io::stdout(), LeaveAlternateScreen, DisableBracketedPaste)?;
+        if self.mouse_captured {
+            execute!(io::stdout(), DisableMouseCapture)?;
+        }
+
+        // The editor may come with arguments, like `code --wait`
+        let mut words = editor.split_whitespace();
+        let status = Command::new(words.next().unwrap_or("vi"))
+            .args(words)
+            .arg(format!("+{}", location.line))
+            .arg(&location.file)
+            .status();
+
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
+        if self.mouse_captured {
+            execute!(io::stdout(), EnableMouseCapture)?;
+        }
+        terminal.clear()?;
+
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => self.status_message = Some(format!("{} exited with {}", editor, status)),
+            Err(e) => self.status_message = Some(format!("Failed to run {}: {}", editor, e)),
+        }
+        Ok(())
+    }
+
+    /// Handle a single key press. Returns `true` when the application should quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        self.status_message = None;
+
+        // Ctrl-c always quits, whatever the keymap says
+        let chord = KeyChord::from_event(&key);
+        if chord.is_interrupt() {
+            return true;
+        }
+
+        let action = self.config.ui.keymap.action(&chord);
+
+        if self.show_help {
+            if matches!(
+                action,
+                Some(Action::Help | Action::ClearSearch | Action::Quit)
+            ) {
+                self.show_help = false;
+            }
+            return false;
+        }
+
+        // Scroll keys scroll the popup, any other key closes it
+        if self.popup.is_some() {
+            match action {
+                Some(Action::Down) => self.popup_scroll = self.popup_scroll.saturating_add(1),
+                Some(Action::Up) => self.popup_scroll = self.popup_scroll.saturating_sub(1),
+                Some(Action::PageDown) => self.popup_scroll = self.popup_scroll.saturating_add(10),
+                Some(Action::PageUp) => self.popup_scroll = self.popup_scroll.saturating_sub(10),
+                _ => self.popup = None,
+            }
+            return false;
+        }
+
+        // While searching, typing and line editing go to the query
+        if self.view == View::List
+            && self.input_mode == InputMode::Insert
+            && self.handle_editing_key(key)
+        {
+            return false;
+        }
+
+        match action {
+            Some(action) => self.run_action(action),
+            None => false,
+        }
+    }
+
+    /// Run what a key is bound to in `ui.keymap`. Returns `true` when the
+    /// application should quit.
+    fn run_action(&mut self, action: Action) -> bool {
+        let keyboard = self.view == View::Keyboard;
+        let searching = self.input_mode == InputMode::Insert;
+        let modal = self.config.ui.input_style == InputStyle::Modal;
+        let columns_step = if self.columns > 1 {
+            self.get_items_per_column() as isize
+        } else {
+            0
+        };
+
+        match action {
+            Action::Quit => return true,
+            Action::ClearSearch if keyboard => self.toggle_keyboard_view(),
+            // Esc clears a non-empty query first, then leaves insert mode
+            // (or quits when there is no normal mode to fall back to)
+            Action::ClearSearch if searching => {
+                if !self.clear_search() {
+                    if modal {
+                        self.input_mode = InputMode::Normal;
+                    } else {
+                        return true;
+                    }
+                }
+            }
+            Action::ClearSearch => return !self.clear_key_filter() && !self.clear_search(),
+            Action::Search if !keyboard => self.input_mode = InputMode::Insert,
+            Action::Search => {}
+            Action::Up if keyboard => self.keyboard.move_up(),
+            Action::Down if keyboard => self.keyboard.move_down(),
+            Action::Left if keyboard => self.keyboard.move_left(),
+            Action::Right if keyboard => self.keyboard.move_right(),
+            Action::Up => self.previous(),
+            Action::Down => self.next(),
+            Action::Left => self.move_selection(-columns_step),
+            Action::Right => self.move_selection(columns_step),
+            Action::PageUp => self.move_selection(-self.page_size()),
+            Action::PageDown => self.move_selection(self.page_size()),
+            Action::Top => self.move_selection(isize::MIN / 2),
+            Action::Bottom => self.move_selection(isize::MAX / 2),
+            Action::Activate if keyboard => self.jump_to_selected_key(),
+            Action::Activate if searching && modal => {
+                self.state.push_history(self.search.value());
+                self.input_mode = InputMode::Normal;
+            }
+            Action::Activate => return self.activate_selected(),
+            Action::Copy => {
+                if let Some(kb) = self.selected_keybinding().cloned() {
+                    self.copy_key(&kb);
+                }
+            }
+            Action::Execute => {
+                if let Some(kb) = self.selected_keybinding().cloned() {
+                    self.state.record_use(&kb.id());
+                    self.exit_action = Some(ExitAction::Execute(kb));
+                    return true;
+                }
+            }
+            Action::Edit => {
+                self.edit_request = self.selected_keybinding().map(|kb| kb.location.clone())
+            }
+            Action::ToggleDetail => self.show_detail(),
+            Action::Pin => self.toggle_pin(),
+            Action::Sort => self.set_sort_mode(self.sort_mode.next()),
+            Action::SortPrevious => self.set_sort_mode(self.sort_mode.previous()),
+            Action::Layout => self.layout = self.layout.next(),
+            Action::ColumnNext => self.pick_column(1),
+            Action::ColumnPrevious => self.pick_column(-1),
+            Action::ColumnWider => self.resize_column(2),
+            Action::ColumnNarrower => self.resize_column(-2),
+            Action::NextTab => self.next_tab(),
+            Action::PreviousTab => self.previous_tab(),
+            Action::Keyboard => self.toggle_keyboard_view(),
+            Action::FreeKeys if keyboard => {
+                let modifiers = self.keyboard.modifiers.clone();
+                self.show_free_keys(&modifiers, None);
+            }
+            Action::FreeKeys => self.show_free_keys_for_selected(),
+            Action::Diagnostics => self.show_diagnostics(),
+            Action::ToggleInactive => self.toggle_inactive(),
+            Action::Help => self.show_help = true,
+            Action::HistoryPrevious => self.recall_history(true),
+            Action::HistoryNext => self.recall_history(false),
+            Action::ToggleSuper | Action::ToggleShift | Action::ToggleCtrl | Action::ToggleAlt => {
+                if let (true, Some(modifier)) = (keyboard, action.modifier()) {
+                    self.keyboard.toggle_modifier(modifier);
+                }
+            }
+            // Only reachable through the search box
+            Action::CursorLeft
+            | Action::CursorRight
+            | Action::WordLeft
+            | Action::WordRight
+            | Action::LineStart
+            | Action::LineEnd
+            | Action::DeleteBack
+            | Action::DeleteForward
+            | Action::DeleteWord
+            | Action::ClearLine => {}
+        }
+        false
+    }
+
+    /// Handle a mouse event. Returns `true` when the application should quit.
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> bool {
+        // Capture is set up once at start; after a reload turns `ui.mouse`
+        // off the events still come and are ignored
+        if !self.config.ui.mouse {
+            return false;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+
+        if self.show_help {
+            if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+                self.show_help = false;
+            }
+            return false;
+        }
+
+        if self.popup.is_some() {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => {
+                    self.popup_scroll = self.popup_scroll.saturating_add(1)
+                }
+                MouseEventKind::ScrollUp => self.popup_scroll = self.popup_scroll.saturating_sub(1),
+                MouseEventKind::Down(MouseButton::Left) => self.popup = None,
+                _ => {}
+            }
+            return false;
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown if self.view == View::List => self.next(),
+            MouseEventKind::ScrollUp if self.view == View::List => self.previous(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if self.click_areas.help.contains(position) {
+                    self.show_help = true;
+                } else if self.click_areas.diagnostics.contains(position) {
+                    self.show_diagnostics();
+                } else if let Some(tab) = self
+                    .click_areas
+                    .tabs
+                    .iter()
+                    .position(|area| area.contains(position))
+                {
+                    self.select_tab(tab);
+                } else if self.view == View::Keyboard {
+                    let Some(&(cursor, _)) = self
+                        .keyboard
+                        .key_areas
+                        .iter()
+                        .find(|(_, area)| area.contains(position))
+                    else {
+                        return false;
+                    };
+                    self.keyboard.cursor = cursor;
+                    if self.is_double_click(ClickTarget::Key(cursor.0, cursor.1)) {
+                        self.jump_to_selected_key();
+                    }
+                } else if let Some(index) = self.item_at(position) {
+                    self.list_state.select(Some(index));
+                    self.update_column_selection();
+                    if self.is_double_click(ClickTarget::Item(index)) {
+                        return self.activate_selected();
+                    }
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Record a click and report whether it completes a double-click on the
+    /// same target.
+    fn is_double_click(&mut self, target: ClickTarget) -> bool {
+        let double_click = self
+            .last_click
+            .is_some_and(|(at, last)| last == target && at.elapsed() <= DOUBLE_CLICK_INTERVAL);
+        self.last_click = if double_click {
+            None
+        } else {
+            Some((Instant::now(), target))
+        };
+        double_click
+    }
+
+    /// Map a screen position to an index into `filtered_keybindings`.
+    fn item_at(&self, position: Position) -> Option<usize> {
+        let (column_idx, column) = self
+            .click_areas
+            .columns
+            .iter()
+            .enumerate()
+            .find(|(_, column)| column.area.contains(position))?;
+
+        // Rows start below the top border
+        let row = position.y.checked_sub(column.area.y + 1)?;
+        let offset = if self.layout == ListLayout::Table {
+            self.table_state.offset()
+        } else if self.columns == 1 {
+            self.list_state.offset()
+        } else {
+            self.column_lists.get(column_idx)?.offset()
+        };
+
+        let mut top = 0;
+        for (relative_idx, height) in column.heights.iter().enumerate().skip(offset) {
+            if row < top + height {
+                return Some(column.start + relative_idx);
+            }
+            top += height;
+        }
+        None
+    }
+
+    /// Pasted text always goes into the search query.
+    fn handle_paste(&mut self, text: &str) {
+        if self.show_help {
+            return;
+        }
+        self.input_mode = InputMode::Insert;
+        self.history_index = None;
+        self.search.insert_str(text);
+        self.filter_keybindings();
+    }
+
+    /// Run the configured Enter action on the selected keybinding. Returns
+    /// `true` when the application should quit.
+    fn activate_selected(&mut self) -> bool {
+        let Some(kb) = self.selected_keybinding().cloned() else {
+            return false;
+        };
+
+        self.state.record_use(&kb.id());
+        self.state.push_history(self.search.value());
+
+        match self.config.ui.enter_action {
+            EnterAction::Select => {
+                self.exit_action = Some(ExitAction::Print(kb));
+                true
+            }
+            EnterAction::Execute => {
+                self.exit_action = Some(ExitAction::Execute(kb));
+                true
+            }
+            EnterAction::Copy => {
+                self.copy_key(&kb);
+                false
+            }
+        }
+    }
+
+    /// The key to mention for `action` in hints, or its name when unbound.
+    fn key_hint(&self, action: Action, searching: bool) -> String {
+        self.config
+            .ui
+            .keymap
+            .hint(action, searching)
+            .unwrap_or_else(|| action.name().to_string())
+    }
+
+    fn selected_keybinding(&self) -> Option<&Keybinding> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.filtered_keybindings.get(i))
+            .map(|(_, kb)| kb)
+    }
+
+    /// A popup with everything known about the selected keybinding.
+    fn show_detail(&mut self) {
+        let Some(kb) = self.selected_keybinding().cloned() else {
+            return;
+        };
+        let mut lines = vec![
+            format!("Key:         {}", kb.key),
+            format!("Description: {}", kb.description),
+            format!("Action:      {}", kb.action),
+            format!("Category:    {}", kb.category),
+            format!("Command:     {}", kb.raw_command),
+            format!("Defined at:  {}", kb.location),
+        ];
+        if !kb.flags.is_empty() {
+            lines.push(format!("Flags:       {}", kb.flags));
+        }
+        if let Some(submap) = &kb.submap {
+            lines.push(format!("Submap:      {}", submap));
+        }
+        if kb.inactive {
+            lines.push("Inactive:    in a false `# hyprlang if` block".to_string());
+        }
+        self.open_popup("Keybinding".to_string(), lines);
+    }
+
+    fn copy_key(&mut self, kb: &Keybinding) {
+        self.status_message = Some(match copy_to_clipboard(&kb.key) {
+            Ok(()) => format!("Copied: {}", kb.key),
+            Err(e) => format!("Copy failed: {:#}", e),
+        });
+    }
+
+    /// Step through the search history. `older` moves towards the oldest
+    /// entry; moving past the newest entry restores the query being typed.
+    fn recall_history(&mut self, older: bool) {
+        let history_len = self.state.history().len();
+        if history_len == 0 {
+            return;
+        }
+
+        let index = match (self.history_index, older) {
+            (None, true) => {
+                self.history_draft = self.search.value().to_string();
+                Some(history_len - 1)
+            }
+            (None, false) => return,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < history_len => Some(i + 1),
+            (Some(_), false) => None,
+        };
+
+        self.history_index = index;
+        let query = match index {
+            Some(i) => self.state.history()[i].clone(),
+            None => std::mem::take(&mut self.history_draft),
+        };
+        self.search.set_value(&query);
+        self.filter_keybindings();
+    }
+
+    /// Pick the table column that the resize keys act on.
+    fn pick_column(&mut self, delta: isize) {
+        let count = self.config.ui.table.columns.len() as isize;
+        if self.layout != ListLayout::Table || count == 0 {
+            return;
+        }
+        let index = match self.table_column {
+            Some(index) => (index as isize + delta).rem_euclid(count),
+            None if delta > 0 => 0,
+            None => count - 1,
+        } as usize;
+        self.table_column = Some(index);
+        self.status_message = Some(format!(
+            "Column: {} ({} wider, {} narrower)",
+            self.config.ui.table.columns[index].label(),
+            self.key_hint(Action::ColumnWider, false),
+            self.key_hint(Action::ColumnNarrower, false)
+        ));
+    }
+
+    /// Widen or narrow the picked table column, from the width it was
+    /// last drawn at.
+    fn resize_column(&mut self, delta: i16) {
+        if self.layout != ListLayout::Table {
+            return;
+        }
+        let index = *self.table_column.get_or_insert(0);
+        let Some(&column) = self.config.ui.table.columns.get(index) else {
+            return;
+        };
+        let current = self
+            .table_widths
+            .get(&column)
+            .or_else(|| self.drawn_widths.get(index))
+            .copied()
+            .unwrap_or(column.label().len() as u16);
+        let width = current.saturating_add_signed(delta).max(3);
+        self.table_widths.insert(column, width);
+        self.status_message = Some(format!("{} column: {} cells", column.label(), width));
+    }
+
+    fn set_sort_mode(&mut self, mode: SortMode) {
+        self.sort_mode = mode;
+        self.filter_keybindings();
+        self.status_message = Some(format!("Sort: {}", self.sort_mode.label()));
+    }
+
+    /// Clear the search query. Returns `false` if it was already empty.
+    fn clear_search(&mut self) -> bool {
+        if self.search.is_empty() {
+            return false;
+        }
+        self.search.clear();
+        self.filter_keybindings();
+        true
+    }
+
+    /// Typing and line editing in the search box. Returns `false` for keys
+    /// that don't edit the query, which then go through the keymap.
+    fn handle_editing_key(&mut self, key: KeyEvent) -> bool {
+        let chord = KeyChord::from_event(&key);
+        let edited = match (key.code, self.config.ui.keymap.editing_action(&chord)) {
+            (KeyCode::Char(c), _) if chord.is_text() => {
+                self.search.insert_char(c);
+                true
+            }
+            (_, Some(Action::DeleteBack)) => self.search.backspace(),
+            (_, Some(Action::DeleteForward)) => self.search.delete(),
+            (_, Some(Action::DeleteWord)) => self.search.delete_word_back(),
+            (_, Some(Action::ClearLine)) => {
+                self.clear_search();
+                false
+            }
+            (_, Some(action)) => {
+                match action {
+                    Action::CursorLeft => self.search.move_left(),
+                    Action::CursorRight => self.search.move_right(),
+                    Action::WordLeft => self.search.move_word_left(),
+                    Action::WordRight => self.search.move_word_right(),
+                    Action::LineStart => self.search.move_home(),
+                    Action::LineEnd => self.search.move_end(),
+                    _ => {}
+                }
+                false
+            }
+            (_, None) => return false,
+        };
+
+        if edited {
+            self.history_index = None;
+            self.filter_keybindings();
+        }
+        true
+    }
+
     fn calculate_columns(&mut self, terminal_width: u16) {
         // Calculate optimal number of columns based on terminal width
         // Minimum width per column: 50 characters (allows for reasonable keybinding display)
         let min_column_width = 50;
-        let new_columns = ((terminal_width as usize).saturating_sub(4) / min_column_width).max(1);
+        let new_columns = match self.layout {
+            ListLayout::Table => 1,
+            ListLayout::List => {
+                ((terminal_width as usize).saturating_sub(4) / min_column_width).max(1)
+            }
+        };
 
         if new_columns != self.columns {
             self.columns = new_columns;
             self.column_lists = vec![ListState::default(); self.columns];
 
-            // Set initial selection in first column
+            // Keep the selection when switching layouts or resizing
             if !self.filtered_keybindings.is_empty() {
-                self.column_lists[0].select(Some(0));
+                self.update_column_selection();
             }
         }
     }
 
+    /// Ranking key for the usage sort modes; smaller sorts first.
+    fn usage_rank(&self, kb: &Keybinding) -> (Reverse<u64>, Reverse<u64>) {
+        let usage = self.state.usage(&kb.id()).cloned().unwrap_or_default();
+        let (count, last_used) = (usage.count as u64, usage.last_used);
+        match self.sort_mode {
+            SortMode::Recent => (Reverse(last_used), Reverse(count)),
+            SortMode::Frequent => (Reverse(count), Reverse(last_used)),
+            _ => (Reverse(0), Reverse(0)),
+        }
+    }
+
+    /// Order two bindings by the current sort mode, pinned bindings first.
+    /// Ties are `Equal`, so a stable sort keeps their previous order.
+    fn compare(&self, a: &Keybinding, b: &Keybinding) -> Ordering {
+        let pinned = |kb: &Keybinding| Reverse(self.state.is_pinned(&kb.id()));
+        let modifiers = |kb: &Keybinding| {
+            let modifiers = kb.modifiers();
+            (modifiers.len(), modifiers)
+        };
+        let dispatcher = |kb: &Keybinding| {
+            let dispatcher = kb.action.split(',').next().unwrap_or_default();
+            dispatcher.trim().to_lowercase()
+        };
+        let text = |text: &str| text.to_lowercase();
+
+        pinned(a)
+            .cmp(&pinned(b))
+            .then_with(|| match self.sort_mode {
+                SortMode::File => Ordering::Equal,
+                SortMode::Key => (a.key_name(), modifiers(a)).cmp(&(b.key_name(), modifiers(b))),
+                SortMode::Modifiers => {
+                    (modifiers(a), a.key_name()).cmp(&(modifiers(b), b.key_name()))
+                }
+                SortMode::Category => text(&a.category).cmp(&text(&b.category)),
+                SortMode::Dispatcher => dispatcher(a).cmp(&dispatcher(b)),
+                SortMode::Description => text(&a.description).cmp(&text(&b.description)),
+                SortMode::Recent | SortMode::Frequent => {
+                    self.usage_rank(a).cmp(&self.usage_rank(b))
+                }
+            })
+    }
+
     fn filter_keybindings(&mut self) {
-        if self.search_query.is_empty() {
-            self.filtered_keybindings = self
+        if self.search.is_empty() {
+            let mut all: Vec<(usize, Keybinding)> = self
                 .keybindings
                 .iter()
                 .enumerate()
+                .filter(|(i, kb)| self.in_current_tab(*i, kb))
                 .map(|(i, kb)| (i, kb.clone()))
                 .collect();
+
+            // Stable, so ties keep their file order
+            all.sort_by(|a, b| self.compare(&a.1, &b.1));
+            self.filtered_keybindings = all;
         } else {
             let mut matches: Vec<(usize, Keybinding, i64)> = self
                 .keybindings
                 .iter()
                 .enumerate()
+                .filter(|(i, kb)| self.in_current_tab(*i, kb))
                 .filter_map(|(i, kb)| {
                     let search_text = format!("{} {} {}", kb.key, kb.action, kb.description);
                     self.matcher
-                        .fuzzy_match(&search_text, &self.search_query)
+                        .fuzzy_match(&search_text, self.search.value())
                         .map(|score| (i, kb.clone(), score))
                 })
                 .collect();
 
-            // Sort by score (higher is better)
-            matches.sort_by(|a, b| b.2.cmp(&a.2));
-
-            // Take up to max_results
-            self.filtered_keybindings = matches
-                .into_iter()
-                .take(self.config.ui.max_results)
-                .map(|(i, kb, _)| (i, kb))
-                .collect();
+            // Pinned first, then the best matches; the sort mode orders
+            // matches that score the same
+            matches.sort_by(|a, b| {
+                let pinned =
+                    |m: &(usize, Keybinding, i64)| Reverse(self.state.is_pinned(&m.1.id()));
+                (pinned(a), Reverse(a.2))
+                    .cmp(&(pinned(b), Reverse(b.2)))
+                    .then_with(|| self.compare(&a.1, &b.1))
+            });
+            matches.truncate(self.config.ui.max_results);
+
+            self.filtered_keybindings = matches.into_iter().map(|(i, kb, _)| (i, kb)).collect();
         }
 
         // Reset selections for all columns
@@ -207,6 +1281,7 @@ impl App {
         self.filtered_keybindings.len().div_ceil(self.columns)
     }
 
+    #[allow(clippy::manual_checked_ops)]
     fn get_current_column(&self) -> usize {
         if let Some(selected) = self.list_state.selected() {
             let items_per_column = self.get_items_per_column();
@@ -248,6 +1323,25 @@ impl App {
         }
     }
 
+    /// Move the selection by `delta` rows, stopping at either end.
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.filtered_keybindings.len();
+        if len == 0 || delta == 0 {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let index = current.saturating_add(delta).clamp(0, len as isize - 1);
+        self.list_state.select(Some(index as usize));
+        self.update_column_selection();
+    }
+
+    /// The rows visible in one list column, for paging.
+    fn page_size(&self) -> isize {
+        self.click_areas.columns.first().map_or(10, |column| {
+            column.area.height.saturating_sub(2).max(1) as isize
+        })
+    }
+
     fn next(&mut self) {
         if self.filtered_keybindings.is_empty() {
             return;
@@ -287,6 +1381,10 @@ impl App {
     }
 
     fn ui(&mut self, f: &mut Frame) {
+        // Paint the theme's background behind everything
+        let theme = self.theme.clone();
+        f.render_widget(Block::default().style(theme.base()), f.area());
+
         if self.show_help {
             self.render_help(f);
             return;
@@ -299,44 +1397,126 @@ impl App {
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Length(3), // Search bar
+                Constraint::Length(1), // Tabs
                 Constraint::Min(0),    // List
-                Constraint::Length(2), // Status bar
+                Constraint::Length(3), // Status bar
             ])
             .split(f.area());
 
         // Search bar
-        let theme = self.config.ui.theme.colors.clone();
+        let mode_label = match self.input_mode {
+            InputMode::Normal => "NORMAL",
+            InputMode::Insert => "INSERT",
+        };
         let search_block = Block::default()
             .borders(Borders::ALL)
-            .title("Search Keybindings")
-            .border_style(Style::default().fg(parse_hex_color(&theme.border_color)));
-
-        let search_text = if self.search_query.is_empty() {
-            "Type to search... (? for help, q to quit)".to_string()
+            .title(format!("Search Keybindings [{}]", mode_label))
+            .border_style(theme.border());
+
+        let search_text = if self.search.is_empty() {
+            match (self.input_mode, self.config.ui.input_style) {
+                (InputMode::Normal, _) => format!(
+                    "Press {} to search... ({} for help, {} to quit)",
+                    self.key_hint(Action::Search, false),
+                    self.key_hint(Action::Help, false),
+                    self.key_hint(Action::Quit, false)
+                ),
+                (InputMode::Insert, InputStyle::Modal) => format!(
+                    "Type to search... ({} for normal mode, {} for help)",
+                    self.key_hint(Action::ClearSearch, true),
+                    self.key_hint(Action::Help, true)
+                ),
+                (InputMode::Insert, InputStyle::Ctrl) => format!(
+                    "Type to search... ({} for help, {} to quit)",
+                    self.key_hint(Action::Help, true),
+                    self.key_hint(Action::ClearSearch, true)
+                ),
+            }
         } else {
-            self.search_query.clone()
+            self.search.value().to_string()
         };
 
-        let search_paragraph = Paragraph::new(search_text).block(search_block).style(
-            if self.search_query.is_empty() {
-                Style::default().fg(parse_hex_color(&theme.description_color))
+        // Scroll horizontally so the cursor stays inside the box
+        let inner_width = chunks[0].width.saturating_sub(2);
+        let cursor = self.search.cursor_width() as u16;
+        let scroll = (cursor + 1).saturating_sub(inner_width);
+
+        let search_paragraph = Paragraph::new(search_text)
+            .block(search_block)
+            .scroll((0, scroll))
+            .style(if self.search.is_empty() {
+                theme.placeholder()
             } else {
-                Style::default().fg(parse_hex_color(&theme.search_fg))
-            },
-        );
+                theme.search()
+            });
 
         f.render_widget(search_paragraph, chunks[0]);
 
-        // Render keybindings in columns
-        self.render_keybindings_columns(f, chunks[1]);
+        if self.input_mode == InputMode::Insert {
+            f.set_cursor_position((chunks[0].x + 1 + cursor - scroll, chunks[0].y + 1));
+        }
+
+        // Tabs, with clickable diagnostics and help hints on the right
+        let help_hint = format!(
+            " {} Help ",
+            self.key_hint(Action::Help, self.input_mode == InputMode::Insert)
+        );
+        let diagnostics_hint = if self.diagnostics.is_empty() {
+            String::new()
+        } else {
+            format!(" ⚠ {} ", self.diagnostics.len())
+        };
+        let tabs_row = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(diagnostics_hint.chars().count() as u16),
+                Constraint::Length(help_hint.chars().count() as u16),
+            ])
+            .split(chunks[1]);
+        self.record_tab_areas(tabs_row[0]);
+        self.click_areas.diagnostics = tabs_row[1];
+        self.click_areas.help = tabs_row[2];
+        f.render_widget(
+            Paragraph::new(diagnostics_hint).style(theme.warning()),
+            tabs_row[1],
+        );
+        f.render_widget(Paragraph::new(help_hint).style(theme.muted()), tabs_row[2]);
+
+        let tabs = Tabs::new(self.tabs.iter().map(Tab::title))
+            .select(self.current_tab)
+            .style(theme.muted())
+            .highlight_style(theme.category());
+        f.render_widget(tabs, tabs_row[0]);
+
+        // Render keybindings in columns, or the keyboard heatmap
+        match self.view {
+            View::List => self.render_keybindings_columns(f, chunks[2]),
+            View::Keyboard => self.keyboard.render(
+                f,
+                chunks[2],
+                &self.keybindings,
+                &self.config.ui.keymap,
+                &theme,
+            ),
+        }
 
         // Status bar
-        let status_text = if let Some(selected) = self.list_state.selected() {
+        let status_text = if let Some(message) = &self.status_message {
+            message.clone()
+        } else if self.view == View::Keyboard {
+            self.keyboard_status()
+        } else if let Some(selected) = self.list_state.selected() {
             if let Some((_, kb)) = self.filtered_keybindings.get(selected) {
-                if self.config.ui.show_raw_command {
+                let status = if self.config.ui.show_raw_command {
                     format!("Raw: {}", kb.raw_command)
                 } else {
                     format!("Action: {}", kb.action)
+                };
+                if kb.inactive {
+                    format!("{} (inactive: in a false `# hyprlang if` block)", status)
+                } else {
+                    status
                 }
             } else {
                 "No selection".to_string()
@@ -349,15 +1529,77 @@ impl App {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(parse_hex_color(&theme.border_color))),
+                    .border_style(theme.border()),
             )
-            .style(Style::default().fg(parse_hex_color(&theme.description_color)));
+            .style(theme.muted());
+
+        f.render_widget(status_paragraph, chunks[3]);
+
+        if let Some((title, lines)) = &self.popup {
+            let popup_area = centered_popup(f.area());
+            f.render_widget(Clear, popup_area);
+            let popup = Paragraph::new(lines.join("\n"))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(title.as_str())
+                        .title_alignment(Alignment::Center)
+                        .border_style(theme.border()),
+                )
+                .wrap(Wrap { trim: false })
+                .scroll((self.popup_scroll, 0))
+                .style(theme.popup());
+            f.render_widget(popup, popup_area);
+        }
+    }
+
+    fn keyboard_status(&self) -> String {
+        let Some(key) = self.keyboard.selected_key() else {
+            return String::new();
+        };
+        let chord = self
+            .keyboard
+            .modifiers
+            .iter()
+            .map(String::as_str)
+            .chain([key.label.as_str()])
+            .collect::<Vec<_>>()
+            .join(" + ");
+
+        match self.keyboard.bindings_for(key, &self.keybindings)[..] {
+            [] => format!("{} is free", chord),
+            [kb] => format!("{}: {}", chord, kb.description),
+            [kb, ref rest @ ..] => format!(
+                "{}: {} (+{} more, {} to list)",
+                chord,
+                kb.description,
+                rest.len(),
+                self.key_hint(Action::Activate, false)
+            ),
+        }
+    }
 
-        f.render_widget(status_paragraph, chunks[2]);
+    /// Work out where each tab title lands, mirroring the `Tabs` widget's
+    /// default one-cell padding on each side and one-cell divider.
+    fn record_tab_areas(&mut self, area: Rect) {
+        let mut x = area.x;
+        self.click_areas.tabs = self
+            .tabs
+            .iter()
+            .map(|tab| {
+                let width = tab.title().chars().count() as u16 + 2;
+                let tab_area = Rect::new(x, area.y, width, 1).intersection(area);
+                x = x.saturating_add(width + 1);
+                tab_area
+            })
+            .collect();
     }
 
     fn render_keybindings_columns(&mut self, f: &mut Frame, area: Rect) {
-        if self.columns == 1 {
+        self.click_areas.columns.clear();
+        if self.layout == ListLayout::Table {
+            self.render_table(f, area);
+        } else if self.columns == 1 {
             // Single column - use the original rendering
             self.render_single_column(f, area);
         } else {
@@ -367,7 +1609,7 @@ impl App {
     }
 
     fn render_single_column(&mut self, f: &mut Frame, area: Rect) {
-        let theme = self.config.ui.theme.colors.clone();
+        let theme = self.theme.clone();
         let selected_idx = self.list_state.selected();
         let available_width = area.width.saturating_sub(4); // Account for borders and padding
 
@@ -380,11 +1622,17 @@ impl App {
                 self.create_list_item(kb, &theme, is_selected, available_width)
             })
             .collect();
+        self.click_areas.columns.push(ColumnArea {
+            area,
+            start: 0,
+            heights: items.iter().map(|item| item.height() as u16).collect(),
+        });
 
         let list_title = format!(
-            "Keybindings ({}/{})",
+            "Keybindings ({}/{}){}",
             self.filtered_keybindings.len(),
-            self.keybindings.len()
+            self.keybindings.len(),
+            self.sort_suffix()
         );
 
         let list = List::new(items)
@@ -392,14 +1640,10 @@ impl App {
                 Block::default()
                     .borders(Borders::ALL)
                     .title(list_title)
-                    .border_style(Style::default().fg(parse_hex_color(&theme.border_color))),
-            )
-            .highlight_style(
-                Style::default()
-                    .bg(parse_hex_color(&theme.selected_bg))
-                    .fg(parse_hex_color(&theme.selected_fg)),
+                    .border_style(theme.border()),
             )
-            .highlight_symbol("> ");
+            .highlight_style(theme.selected())
+            .highlight_symbol(theme.highlight_symbol.as_str());
 
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
@@ -416,7 +1660,7 @@ impl App {
             .split(area);
 
         let items_per_column = self.get_items_per_column();
-        let theme = self.config.ui.theme.colors.clone();
+        let theme = self.theme.clone();
         let filtered_len = self.filtered_keybindings.len();
         let total_len = self.keybindings.len();
         let columns = self.columns;
@@ -442,11 +1686,22 @@ impl App {
                         self.create_list_item(kb, &theme, is_selected, available_width)
                     })
                     .collect();
+                self.click_areas.columns.push(ColumnArea {
+                    area: chunk,
+                    start: start_idx,
+                    heights: column_items
+                        .iter()
+                        .map(|item| item.height() as u16)
+                        .collect(),
+                });
 
                 let list_title = if col_idx == 0 {
                     format!(
-                        "Keybindings ({}/{}) - {} columns",
-                        filtered_len, total_len, columns
+                        "Keybindings ({}/{}) - {} columns{}",
+                        filtered_len,
+                        total_len,
+                        columns,
+                        self.sort_suffix()
                     )
                 } else {
                     "".to_string()
@@ -461,16 +1716,10 @@ impl App {
                                 Borders::TOP | Borders::BOTTOM | Borders::RIGHT
                             })
                             .title(list_title)
-                            .border_style(
-                                Style::default().fg(parse_hex_color(&theme.border_color)),
-                            ),
+                            .border_style(theme.border()),
                     )
-                    .highlight_style(
-                        Style::default()
-                            .bg(parse_hex_color(&theme.selected_bg))
-                            .fg(parse_hex_color(&theme.selected_fg)),
-                    )
-                    .highlight_symbol("> ");
+                    .highlight_style(theme.selected())
+                    .highlight_symbol(theme.highlight_symbol.as_str());
 
                 if col_idx < self.column_lists.len() {
                     f.render_stateful_widget(list, chunk, &mut self.column_lists[col_idx]);
@@ -481,74 +1730,369 @@ impl App {
         }
     }
 
+    /// One row per binding in aligned columns, under a header marking the
+    /// column the list is sorted by.
+    fn render_table(&mut self, f: &mut Frame, area: Rect) {
+        let theme = self.theme.clone();
+        let columns = self.config.ui.table.columns.clone();
+        let split_mods = columns.contains(&TableColumn::Mods);
+
+        // Lay the columns out the way `Table` will, to cut cells to fit
+        let constraints: Vec<Constraint> = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| self.column_constraint(*column, index == 0, split_mods))
+            .collect();
+        let symbol_width = Span::raw(theme.highlight_symbol.as_str()).width() as u16;
+        let inner_width = area.width.saturating_sub(2).saturating_sub(symbol_width);
+        self.drawn_widths = Layout::horizontal(constraints.clone())
+            .spacing(1)
+            .split(Rect::new(0, 0, inner_width, 1))
+            .iter()
+            .map(|rect| rect.width)
+            .collect();
+
+        let header = Row::new(columns.iter().enumerate().map(|(index, column)| {
+            let mut label = column.label().to_string();
+            if column.sort_mode() == Some(self.sort_mode) {
+                label.push_str(" ▲");
+            }
+            let style = if self.table_column == Some(index) {
+                theme.category().patch(theme.selected())
+            } else {
+                theme.category()
+            };
+            let width = self.drawn_widths[index] as usize;
+            Cell::from(Line::from(fit_spans(
+                &[(&label, 0, style)],
+                &[],
+                style,
+                width,
+            )))
+        }));
+
+        let rows: Vec<Row> = self
+            .filtered_keybindings
+            .iter()
+            .map(|(_, kb)| {
+                Row::new(columns.iter().enumerate().map(|(index, column)| {
+                    let width = self.drawn_widths[index] as usize;
+                    Cell::from(self.table_cell(kb, *column, index == 0, split_mods, width, &theme))
+                }))
+            })
+            .collect();
+
+        self.click_areas.columns.push(ColumnArea {
+            // The header takes the place of the top border for clicks
+            area: Rect {
+                y: area.y + 1,
+                height: area.height.saturating_sub(1),
+                ..area
+            },
+            start: 0,
+            heights: vec![1; rows.len()],
+        });
+
+        let title = format!(
+            "Keybindings ({}/{}){}",
+            self.filtered_keybindings.len(),
+            self.keybindings.len(),
+            self.sort_suffix()
+        );
+        let table = Table::new(rows, constraints)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(theme.border()),
+            )
+            .row_highlight_style(theme.selected())
+            .highlight_symbol(theme.highlight_symbol.as_str())
+            .highlight_spacing(HighlightSpacing::Always);
+
+        self.table_state.select(self.list_state.selected());
+        f.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    /// The width of a table column: set in `ui.table.widths` or by
+    /// resizing, else room for its widest cell over all bindings so the
+    /// columns stay put while searching. Description and params share
+    /// what is left.
+    fn column_constraint(&self, column: TableColumn, first: bool, split_mods: bool) -> Constraint {
+        if let Some(width) = self.table_widths.get(&column) {
+            return Constraint::Length(*width);
+        }
+        let cap = match column {
+            TableColumn::Description => return Constraint::Fill(3),
+            TableColumn::Params => return Constraint::Fill(2),
+            TableColumn::Key | TableColumn::File => 32,
+            _ => 20,
+        };
+        let widest = self
+            .keybindings
+            .iter()
+            .map(|kb| Span::raw(self.cell_text(kb, column, split_mods)).width())
+            .chain([column.label().len() + 2])
+            .max()
+            .unwrap_or(0);
+        // Room for the pin marker
+        let pin = if first { 2 } else { 0 };
+        Constraint::Length((widest + pin).min(cap) as u16)
+    }
+
+    /// What a table cell shows, before highlighting and truncation.
+    fn cell_text(&self, kb: &Keybinding, column: TableColumn, split_mods: bool) -> String {
+        let (modifiers, key) = kb.key.rsplit_once(" + ").unwrap_or(("", &kb.key));
+        let (dispatcher, params) = kb.action.split_once(',').unwrap_or((&kb.action, ""));
+        match column {
+            TableColumn::Key if split_mods => key.to_string(),
+            TableColumn::Key => kb.key.clone(),
+            TableColumn::Mods => modifiers.to_string(),
+            TableColumn::Description
+                if self.config.ui.show_descriptions && !kb.description.is_empty() =>
+            {
+                kb.description.clone()
+            }
+            TableColumn::Description => kb.action.clone(),
+            TableColumn::Dispatcher => dispatcher.trim().to_string(),
+            TableColumn::Params => params.trim().to_string(),
+            TableColumn::Category => kb.category.clone(),
+            TableColumn::Flags => kb.flags.clone(),
+            TableColumn::File => {
+                let file = kb.location.file.file_name().unwrap_or_default();
+                format!("{}:{}", file.to_string_lossy(), kb.location.line)
+            }
+        }
+    }
+
+    /// A table cell, styled like the same part of a list item and cut to
+    /// `width` cells.
+    fn table_cell(
+        &self,
+        kb: &Keybinding,
+        column: TableColumn,
+        first: bool,
+        split_mods: bool,
+        width: usize,
+        theme: &Theme,
+    ) -> Line<'static> {
+        let style = |style: Style| {
+            if kb.inactive {
+                theme.inactive()
+            } else {
+                style
+            }
+        };
+        let show_description = self.config.ui.show_descriptions && !kb.description.is_empty();
+        let (key_matches, text_matches) = self.match_positions(kb, show_description);
+        let text = self.cell_text(kb, column, split_mods);
+
+        // Where the key starts in `kb.key`, which the key matches count from
+        let modifiers_len = kb
+            .key
+            .rsplit_once(" + ")
+            .map_or(0, |(modifiers, _)| modifiers.chars().count() + 3);
+        let (segments, matches): (Vec<_>, &[usize]) = match column {
+            TableColumn::Key if split_mods => (
+                vec![(text.as_str(), modifiers_len, style(theme.key()))],
+                key_matches.as_slice(),
+            ),
+            TableColumn::Key => {
+                let (modifiers, key) = text.split_at(text.rfind(" + ").map_or(0, |i| i + 3));
+                (
+                    vec![
+                        (modifiers, 0, style(theme.modifier())),
+                        (key, modifiers_len, style(theme.key())),
+                    ],
+                    key_matches.as_slice(),
+                )
+            }
+            TableColumn::Mods => (
+                vec![(text.as_str(), 0, style(theme.modifier()))],
+                key_matches.as_slice(),
+            ),
+            TableColumn::Description => {
+                let text_style = if show_description {
+                    theme.description()
+                } else {
+                    theme.action()
+                };
+                (
+                    vec![(text.as_str(), 0, style(text_style))],
+                    text_matches.as_slice(),
+                )
+            }
+            TableColumn::Dispatcher | TableColumn::Params => {
+                (vec![(text.as_str(), 0, style(theme.action()))], &[])
+            }
+            TableColumn::Category => (vec![(text.as_str(), 0, style(theme.category()))], &[]),
+            TableColumn::Flags => (vec![(text.as_str(), 0, style(theme.flags()))], &[]),
+            TableColumn::File => (vec![(text.as_str(), 0, style(theme.muted()))], &[]),
+        };
+
+        let mut spans = Vec::new();
+        let mut width = width;
+        if first && self.state.is_pinned(&kb.id()) {
+            spans.push(Span::styled("★ ", theme.matched()));
+            width = width.saturating_sub(2);
+        }
+        spans.extend(fit_spans(&segments, matches, theme.matched(), width));
+        Line::from(spans)
+    }
+
+    fn sort_suffix(&self) -> String {
+        let mut suffix = match self.sort_mode {
+            SortMode::File => String::new(),
+            mode => format!(" - sorted by {}", mode.label()),
+        };
+        if let Some(filter) = &self.key_filter {
+            let chord: Vec<&str> = filter
+                .modifiers
+                .iter()
+                .map(String::as_str)
+                .chain([filter.key.as_str()])
+                .collect();
+            suffix.push_str(&format!(
+                " - key {} ({} to clear)",
+                chord.join(" + "),
+                self.key_hint(Action::ClearSearch, false)
+            ));
+        }
+        suffix
+    }
+
     fn create_list_item<'a>(
         &self,
         kb: &'a Keybinding,
-        theme: &crate::config::ThemeColors,
+        theme: &Theme,
         is_selected: bool,
         available_width: u16,
     ) -> ListItem<'a> {
-        let key_style = Style::default()
-            .fg(parse_hex_color(&theme.key_color))
-            .add_modifier(Modifier::BOLD);
-        let description_style = Style::default().fg(parse_hex_color(&theme.action_color));
-
-        let content = if self.config.ui.show_descriptions && !kb.description.is_empty() {
-            let key_part = format!("{} → ", kb.key);
-            let key_len = key_part.len() as u16;
-            let description_width = available_width.saturating_sub(key_len + 4); // 4 for padding/borders
-
-            let description_text = if is_selected {
-                // For selected items, allow wrapping by splitting into multiple lines
-                self.wrap_text(&kb.description, description_width)
+        let style = |style: Style| {
+            if kb.inactive {
+                theme.inactive()
             } else {
-                // For unselected items, truncate with ellipsis
-                vec![self.truncate_text(&kb.description, description_width)]
-            };
-
-            let mut lines = vec![Line::from(vec![
-                Span::styled(kb.key.clone(), key_style),
-                Span::raw(" → "),
-                Span::styled(description_text[0].clone(), description_style),
-            ])];
-
-            // Add additional lines for wrapped text (only for selected items)
-            for line in description_text.iter().skip(1) {
-                lines.push(Line::from(vec![
-                    Span::raw(" ".repeat(key_len as usize)),
-                    Span::styled(line.clone(), description_style),
-                ]));
+                style
             }
+        };
+        let show_description = self.config.ui.show_descriptions && !kb.description.is_empty();
+        let (text, text_style) = if show_description {
+            (&kb.description, style(theme.description()))
+        } else {
+            (&kb.action, style(theme.action()))
+        };
+        let (key_matches, text_matches) = self.match_positions(kb, show_description);
 
-            lines
+        let mut head = Vec::new();
+        if self.state.is_pinned(&kb.id()) {
+            head.push(Span::styled("★ ", theme.matched()));
+        }
+        let (modifiers, key) = match kb.key.rsplit_once(" + ") {
+            Some((modifiers, key)) => (format!("{} + ", modifiers), key),
+            None => (String::new(), kb.key.as_str()),
+        };
+        let modifiers_len = modifiers.chars().count();
+        head.extend(highlight_spans(
+            &modifiers,
+            0,
+            &key_matches,
+            style(theme.modifier()),
+            theme.matched(),
+        ));
+        head.extend(highlight_spans(
+            key,
+            modifiers_len,
+            &key_matches,
+            style(theme.key()),
+            theme.matched(),
+        ));
+        if !kb.flags.is_empty() {
+            head.push(Span::styled(
+                format!(" [{}]", kb.flags),
+                style(theme.flags()),
+            ));
+        }
+        head.push(Span::styled(
+            theme.separator.clone(),
+            style(theme.separator()),
+        ));
+        let head_width: usize = head.iter().map(Span::width).sum();
+        let text_width = available_width.saturating_sub(head_width as u16 + 4); // 4 for padding/borders
+
+        let text_lines = if is_selected {
+            // For selected items, allow wrapping by splitting into multiple lines
+            self.wrap_text(text, text_width)
         } else {
-            let key_part = format!("{} → ", kb.key);
-            let key_len = key_part.len() as u16;
-            let action_width = available_width.saturating_sub(key_len + 4);
+            // For unselected items, truncate with ellipsis
+            vec![self.truncate_text(text, text_width)]
+        };
 
-            let action_text = if is_selected {
-                self.wrap_text(&kb.action, action_width)
-            } else {
-                vec![self.truncate_text(&kb.action, action_width)]
+        // Where each line starts in `text`, to line up the matched characters
+        let mut search_from = 0;
+        let mut lines = Vec::new();
+        for (index, line) in text_lines.iter().enumerate() {
+            let visible = match line.strip_suffix("...") {
+                Some(prefix) if !text.ends_with(line.as_str()) => prefix,
+                _ => line.as_str(),
+            };
+            let offset = text[search_from..].find(visible).map(|start| {
+                search_from += start + visible.len();
+                text[..search_from - visible.len()].chars().count()
+            });
+            let mut spans = match offset {
+                Some(offset) => {
+                    highlight_spans(visible, offset, &text_matches, text_style, theme.matched())
+                }
+                None => vec![Span::styled(visible.to_string(), text_style)],
             };
+            if visible.len() < line.len() {
+                spans.push(Span::styled("...", text_style));
+            }
 
-            let mut lines = vec![Line::from(vec![
-                Span::styled(kb.key.clone(), key_style),
-                Span::raw(" → "),
-                Span::styled(action_text[0].clone(), description_style),
-            ])];
+            let mut line_spans = if index == 0 {
+                std::mem::take(&mut head)
+            } else {
+                vec![Span::raw(" ".repeat(head_width))]
+            };
+            line_spans.extend(spans);
+            lines.push(Line::from(line_spans));
+        }
 
-            for line in action_text.iter().skip(1) {
-                lines.push(Line::from(vec![
-                    Span::raw(" ".repeat(key_len as usize)),
-                    Span::styled(line.clone(), description_style),
-                ]));
-            }
+        ListItem::new(lines)
+    }
 
-            lines
+    /// The positions of the characters matching the search query in the key
+    /// and in the description or action shown, as matched when filtering.
+    fn match_positions(&self, kb: &Keybinding, show_description: bool) -> (Vec<usize>, Vec<usize>) {
+        if self.search.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+        let search_text = format!("{} {} {}", kb.key, kb.action, kb.description);
+        let Some((_, indices)) = self
+            .matcher
+            .fuzzy_indices(&search_text, self.search.value())
+        else {
+            return (Vec::new(), Vec::new());
         };
 
-        ListItem::new(content)
+        let key_len = kb.key.chars().count();
+        let action_start = key_len + 1;
+        let (text_start, text_len) = if show_description {
+            (
+                action_start + kb.action.chars().count() + 1,
+                kb.description.chars().count(),
+            )
+        } else {
+            (action_start, kb.action.chars().count())
+        };
+        let key_matches = indices.iter().copied().filter(|&i| i < key_len).collect();
+        let text_matches = indices
+            .iter()
+            .filter(|&&i| i >= text_start && i < text_start + text_len)
+            .map(|i| i - text_start)
+            .collect();
+        (key_matches, text_matches)
     }
 
     fn truncate_text(&self, text: &str, max_width: u16) -> String {
@@ -600,69 +2144,811 @@ impl App {
     }
 
     fn render_help(&self, f: &mut Frame) {
-        let area = f.area();
-
-        // Create a centered popup
-        let popup_area = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(20),
-                Constraint::Percentage(60),
-                Constraint::Percentage(20),
-            ])
-            .split(area)[1];
-
-        let popup_area = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(20),
-                Constraint::Percentage(60),
-                Constraint::Percentage(20),
-            ])
-            .split(popup_area)[1];
-
+        let popup_area = centered_popup(f.area());
         f.render_widget(Clear, popup_area);
 
-        let help_text = vec![
-            "Hypr-showkey Help",
-            "",
-            "Navigation:",
-            "  ↑/k       - Move up",
-            "  ↓/j       - Move down",
-            "  Enter     - Select keybinding",
-            "",
-            "Search:",
-            "  Type      - Search keybindings",
-            "  Backspace - Delete search character",
-            "",
-            "Display:",
-            "  Auto      - Columns adapt to terminal width",
-            "            - Min 50 chars per column",
-            "            - Unbound keys are filtered out",
-            "",
-            "General:",
-            "  ?/F1      - Toggle this help",
-            "  Esc       - Close help/Clear search",
-            "  q         - Quit application",
-            "",
-            "Search supports fuzzy matching across:",
-            "- Key combinations",
-            "- Action descriptions",
-            "- Categories",
-            "",
-            "Press Esc to close this help.",
-        ];
+        let mut help_text: Vec<String> = ["Hypr-showkey Help", "", "Keys (ui.keymap):"]
+            .map(String::from)
+            .to_vec();
+        help_text.extend(self.config.ui.keymap.help_lines(false));
+        help_text.extend(
+            [
+                "",
+                "In the keyboard view, the movement keys move",
+                "between keys, Enter lists the bindings for the",
+                "key and Esc goes back to the list.",
+                "",
+                "Search box (ui.keymap):",
+            ]
+            .map(String::from),
+        );
+        help_text.extend(self.config.ui.keymap.help_lines(true));
+        help_text.extend(
+            [
+                "Keys that type a character search instead",
+                "of running what they are bound to.",
+                "",
+                "Display:",
+                "  Auto      - Columns adapt to terminal width",
+                "            - Min 50 chars per column",
+                "            - Unbound keys are filtered out",
+                "",
+                "Mouse (ui.mouse):",
+                "  Click     - Select keybinding, tab or help",
+                "  Dbl-click - Run the Enter action",
+                "  Wheel     - Move up/down",
+                "",
+                "Ctrl-c always quits.",
+                "",
+                "Set ui.input_style: ctrl to always search and",
+                "navigate with Ctrl-j/k instead of modes;",
+                "Enter then runs the Enter action.",
+                "",
+                "Search supports fuzzy matching across:",
+                "- Key combinations",
+                "- Action descriptions",
+                "- Categories",
+                "",
+            ]
+            .map(String::from),
+        );
+        help_text.push(format!(
+            "Press {} to close this help.",
+            self.key_hint(Action::Help, false)
+        ));
 
         let help_paragraph = Paragraph::new(help_text.join("\n"))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .title("Help")
-                    .title_alignment(Alignment::Center),
+                    .title_alignment(Alignment::Center)
+                    .border_style(self.theme.border()),
             )
             .wrap(Wrap { trim: true })
-            .style(Style::default().fg(Color::White));
+            .style(self.theme.popup());
 
         f.render_widget(help_paragraph, popup_area);
     }
 }
+
+/// `segments` of (text, position of its first character in the matched
+/// text, style) highlighted like [`highlight_spans`], cut with an ellipsis
+/// when they don't fit in `width` cells.
+fn fit_spans(
+    segments: &[(&str, usize, Style)],
+    positions: &[usize],
+    matched: Style,
+    width: usize,
+) -> Vec<Span<'static>> {
+    let char_width = |c: char| Span::raw(c.to_string()).width();
+    let total: usize = segments
+        .iter()
+        .map(|(text, _, _)| text.chars().map(char_width).sum::<usize>())
+        .sum();
+    let mut budget = if total <= width {
+        width
+    } else {
+        width.saturating_sub(1)
+    };
+
+    let mut spans = Vec::new();
+    let mut last_style = Style::default();
+    for (text, offset, style) in segments {
+        let mut end = 0;
+        for (i, c) in text.char_indices() {
+            let c_width = char_width(c);
+            if c_width > budget {
+                break;
+            }
+            budget -= c_width;
+            end = i + c.len_utf8();
+        }
+        spans.extend(highlight_spans(
+            &text[..end],
+            *offset,
+            positions,
+            *style,
+            matched,
+        ));
+        last_style = *style;
+        if end < text.len() {
+            break;
+        }
+    }
+    if total > width && width > 0 {
+        spans.push(Span::styled("…", last_style));
+    }
+    spans
+}
+
+/// `text` in `style`, with the characters at `positions` (counted from
+/// `offset`) in the matched style on top.
+fn highlight_spans<'a>(
+    text: &str,
+    offset: usize,
+    positions: &[usize],
+    style: Style,
+    matched: Style,
+) -> Vec<Span<'a>> {
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_matched = positions.contains(&(offset + i));
+        if is_matched != run_matched && !run.is_empty() {
+            let run_style = if run_matched {
+                style.patch(matched)
+            } else {
+                style
+            };
+            spans.push(Span::styled(std::mem::take(&mut run), run_style));
+        }
+        run_matched = is_matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        let run_style = if run_matched {
+            style.patch(matched)
+        } else {
+            style
+        };
+        spans.push(Span::styled(run, run_style));
+    }
+    spans
+}
+
+/// The middle 60% of `area`, for popups.
+fn centered_popup(area: Rect) -> Rect {
+    let popup_area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(20),
+            Constraint::Percentage(60),
+            Constraint::Percentage(20),
+        ])
+        .split(area)[1];
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(20),
+            Constraint::Percentage(60),
+            Constraint::Percentage(20),
+        ])
+        .split(popup_area)[1]
+}
+
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut child = Command::new("wl-copy")
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to run wl-copy")?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Run a keybinding's dispatcher through `hyprctl dispatch`.
+fn dispatch(kb: &Keybinding) -> Result<()> {
+    let (dispatcher, params) = kb
+        .action
+        .split_once(',')
+        .map(|(d, p)| (d.trim(), p.trim()))
+        .unwrap_or((kb.action.trim(), ""));
+
+    let mut command = Command::new("hyprctl");
+    command.arg("dispatch").arg(dispatcher);
+    if !params.is_empty() {
+        command.arg(params);
+    }
+
+    let status = command.status().context("Failed to run hyprctl")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("hyprctl dispatch {} failed", dispatcher));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+    use crossterm::event::KeyModifiers;
+
+    fn test_app(input_style: &str) -> App {
+        let config: Config = serde_yaml::from_str(&format!(
+            "hyprland_configs:\n  files: []\nui:\n  input_style: {}\n",
+            input_style
+        ))
+        .unwrap();
+        let keybindings = ["kitty", "quit"]
+            .iter()
+            .map(|name| Keybinding {
+                key: format!("Super + {}", name[..1].to_uppercase()),
+                action: format!("exec, {}", name),
+                description: format!("Launch {}", name),
+                category: "Other".to_string(),
+                raw_command: String::new(),
+                submap: None,
+                ..Default::default()
+            })
+            .collect();
+        App::new(keybindings, &config, State::default())
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> bool {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_insert_mode_searches_vim_keys() {
+        let mut app = test_app("modal");
+        for c in "kq".chars() {
+            assert!(!press(&mut app, KeyCode::Char(c)));
+        }
+        assert_eq!(app.search.value(), "kq");
+    }
+
+    #[test]
+    fn test_normal_mode_navigates_and_quits() {
+        let mut app = test_app("modal");
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.input_mode, InputMode::Normal);
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.list_state.selected(), Some(1));
+        assert!(app.search.value().is_empty());
+        press(&mut app, KeyCode::Char('/'));
+        assert_eq!(app.input_mode, InputMode::Insert);
+        press(&mut app, KeyCode::Esc);
+        assert!(press(&mut app, KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_esc_clears_query_before_leaving_insert() {
+        let mut app = test_app("modal");
+        app.handle_paste("kitty\n");
+        assert_eq!(app.search.value(), "kitty");
+        assert_eq!(app.filtered_keybindings.len(), 1);
+        press(&mut app, KeyCode::Esc);
+        assert!(app.search.is_empty());
+        assert_eq!(app.input_mode, InputMode::Insert);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn test_history_recall_restores_draft() {
+        let mut app = test_app("modal");
+        app.state.push_history("kitty");
+        app.state.push_history("quit");
+        app.handle_paste("ki");
+
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        app.handle_key(ctrl('p'));
+        assert_eq!(app.search.value(), "quit");
+        app.handle_key(ctrl('p'));
+        app.handle_key(ctrl('p'));
+        assert_eq!(app.search.value(), "kitty");
+        app.handle_key(ctrl('n'));
+        app.handle_key(ctrl('n'));
+        assert_eq!(app.search.value(), "ki");
+    }
+
+    #[test]
+    fn test_frequent_sort_puts_used_bindings_first() {
+        let mut app = test_app("modal");
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('j'));
+        assert!(press(&mut app, KeyCode::Enter));
+        assert!(matches!(
+            &app.exit_action,
+            Some(ExitAction::Print(kb)) if kb.action == "exec, quit"
+        ));
+
+        // File order stays the default; frequent is opted into
+        let state = std::mem::take(&mut app.state);
+        let mut config = app.config.clone();
+        let app = App::new(app.keybindings.clone(), &config, State::default());
+        assert_eq!(app.filtered_keybindings[0].1.action, "exec, kitty");
+        config.ui.sort = SortMode::Frequent;
+        let app = App::new(app.keybindings.clone(), &config, state);
+        assert_eq!(app.filtered_keybindings[0].1.action, "exec, quit");
+    }
+
+    #[test]
+    fn test_reload_keeps_query_and_selection() {
+        let mut app = test_app("modal");
+        for c in "exec".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Down);
+        let selected = app.filtered_keybindings[1].1.clone();
+
+        // The selected binding moves to the end and a new one appears first
+        let mut keybindings = app.keybindings.clone();
+        keybindings.retain(|kb| kb.id() != selected.id());
+        keybindings.insert(
+            0,
+            Keybinding {
+                key: "Super + E".to_string(),
+                action: "exec, editor".to_string(),
+                ..selected.clone()
+            },
+        );
+        keybindings.push(selected.clone());
+        let config = app.config.clone();
+        app.apply_reload(config, keybindings);
+
+        assert_eq!(app.search.value(), "exec");
+        assert_eq!(app.filtered_keybindings.len(), 3);
+        let position = app.list_state.selected().unwrap();
+        assert_eq!(app.filtered_keybindings[position].1.id(), selected.id());
+    }
+
+    #[test]
+    fn test_reload_waits_for_writes_to_settle_and_reports_warnings() {
+        let dir = testutil::TempDir::new("reload");
+        let binds = dir.write("binds.conf", "bind = SUPER, Q, killactive\n");
+        let config_file = dir.write(
+            "showkey.yaml",
+            &format!(
+                "hyprland_configs:\n  base_dir: {:?}\n  files: [binds.conf, gone.conf]\n\
+                 ui:\n  theme: no_such_theme\n",
+                dir.path()
+            ),
+        );
+
+        let mut app = test_app("modal");
+        app.watch(
+            Some(config_file),
+            std::slice::from_ref(&binds),
+            HashMap::new(),
+        );
+        std::fs::write(&binds, "bind = SUPER, W, killactive\n").unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        // The change is noticed, but only taken once nothing else changes
+        assert!(!app.files_changed());
+        assert!(app.hot_reload.as_ref().unwrap().changed_at.is_some());
+        std::thread::sleep(RELOAD_DEBOUNCE);
+        assert!(app.files_changed());
+        assert!(!app.files_changed());
+
+        app.reload();
+        assert_eq!(app.keybindings.len(), 1);
+        let status = app.status_message.clone().unwrap();
+        assert!(
+            status.starts_with("Reloaded with 2 warnings: "),
+            "{}",
+            status
+        );
+        assert!(
+            status.ends_with("falling back to catppuccin_mocha"),
+            "{}",
+            status
+        );
+    }
+
+    #[test]
+    fn test_editing_an_imported_scheme_rethemes() {
+        let dir = testutil::TempDir::new("retheme");
+        let binds = dir.write("binds.conf", "bind = SUPER, Q, killactive\n");
+        let scheme = dir.write(
+            "scheme.yaml",
+            &(0..16)
+                .map(|i| format!("base0{:X}: \"101010\"\n", i))
+                .collect::<String>(),
+        );
+        let config_file = dir.write(
+            "showkey.yaml",
+            &format!(
+                "hyprland_configs:\n  files: [{:?}]\nui:\n  theme:\n    base16: {:?}\n",
+                binds, scheme
+            ),
+        );
+        let config = Config::load_from(&config_file, appearance::known).unwrap();
+        let mut app = App::new(Vec::new(), &config, State::default());
+        app.watch(
+            Some(config_file),
+            std::slice::from_ref(&binds),
+            HashMap::new(),
+        );
+
+        std::fs::write(
+            &scheme,
+            std::fs::read_to_string(&scheme)
+                .unwrap()
+                .replace("101010", "202020"),
+        )
+        .unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!app.files_changed());
+        std::thread::sleep(RELOAD_DEBOUNCE);
+        assert!(app.files_changed());
+        app.reload();
+        assert_eq!(app.config.ui.theme.colors.background, "#202020");
+        assert_eq!(app.keybindings.len(), 1);
+    }
+
+    #[test]
+    fn test_pinned_bindings_sort_first_and_fill_pinned_tab() {
+        let mut app = test_app("modal");
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('m'));
+
+        assert_eq!(app.filtered_keybindings[0].1.action, "exec, quit");
+        assert_eq!(app.list_state.selected(), Some(0));
+
+        app.show_pinned();
+        assert_eq!(app.filtered_keybindings.len(), 1);
+        press(&mut app, KeyCode::Char('*'));
+        assert!(app.filtered_keybindings.is_empty());
+
+        // Still first while searching, over a better match
+        app.select_tab(0);
+        press(&mut app, KeyCode::Char('m'));
+        app.handle_paste("uit");
+        let actions: Vec<&str> = app
+            .filtered_keybindings
+            .iter()
+            .map(|(_, kb)| kb.action.as_str())
+            .collect();
+        assert_eq!(actions, ["exec, kitty", "exec, quit"]);
+    }
+
+    #[test]
+    fn test_sort_modes_keep_ties_in_file_order() {
+        let mut app = test_app("modal");
+        app.keybindings.push(Keybinding {
+            key: "Super + Shift + A".to_string(),
+            action: "killactive".to_string(),
+            description: "Close window".to_string(),
+            category: "Windows".to_string(),
+            ..Default::default()
+        });
+        let order = |app: &App| -> Vec<String> {
+            app.filtered_keybindings
+                .iter()
+                .map(|(_, kb)| kb.key.clone())
+                .collect()
+        };
+
+        app.filter_keybindings();
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.sort_mode, SortMode::File);
+        assert_eq!(order(&app), ["Super + K", "Super + Q", "Super + Shift + A"]);
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.sort_mode, SortMode::Key);
+        assert_eq!(order(&app), ["Super + Shift + A", "Super + K", "Super + Q"]);
+
+        app.set_sort_mode(SortMode::Modifiers);
+        assert_eq!(order(&app), ["Super + K", "Super + Q", "Super + Shift + A"]);
+
+        // Same category: file order
+        app.set_sort_mode(SortMode::Category);
+        assert_eq!(order(&app), ["Super + K", "Super + Q", "Super + Shift + A"]);
+
+        // While searching, the best match first and the mode breaks ties
+        app.set_sort_mode(SortMode::Description);
+        app.handle_paste("quit");
+        assert_eq!(order(&app)[0], "Super + Q");
+        app.set_sort_mode(SortMode::Key);
+        app.search.set_value("Super");
+        app.filter_keybindings();
+        assert_eq!(order(&app), ["Super + Shift + A", "Super + K", "Super + Q"]);
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('S'));
+        assert_eq!(app.sort_mode, SortMode::File);
+    }
+
+    #[test]
+    fn test_mouse_clicks_map_to_items_and_tabs() {
+        let mut app = test_app("modal");
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        // Search box (3 rows) and tab bar, then the list border
+        assert!(!app.handle_mouse(click(10, 6)));
+        assert_eq!(app.list_state.selected(), Some(1));
+        assert!(app.handle_mouse(click(10, 6)));
+
+        let pinned = app.click_areas.tabs[1];
+        app.handle_mouse(click(pinned.x + 1, pinned.y));
+        assert_eq!(app.tabs[app.current_tab], Tab::Pinned);
+    }
+
+    #[test]
+    fn test_mouse_scrolls_and_only_double_clicks_one_item() {
+        let mut app = test_app("modal");
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let event = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        let click = |column, row| event(MouseEventKind::Down(MouseButton::Left), column, row);
+
+        assert_eq!(app.list_state.selected(), Some(0));
+        app.handle_mouse(event(MouseEventKind::ScrollDown, 0, 0));
+        assert_eq!(app.list_state.selected(), Some(1));
+        app.handle_mouse(event(MouseEventKind::ScrollUp, 0, 0));
+        assert_eq!(app.list_state.selected(), Some(0));
+
+        // Clicking one item and then another is two single clicks
+        assert!(!app.handle_mouse(click(10, 5)));
+        assert!(!app.handle_mouse(click(10, 6)));
+        assert_eq!(app.list_state.selected(), Some(1));
+
+        // Below the last item nothing changes
+        assert!(!app.handle_mouse(click(10, 12)));
+        assert_eq!(app.list_state.selected(), Some(1));
+
+        // The help hint opens the help, and any click closes it again
+        let help = app.click_areas.help;
+        app.handle_mouse(click(help.x, help.y));
+        assert!(app.show_help);
+        assert!(!app.handle_mouse(click(10, 6)));
+        assert!(!app.show_help);
+        assert_eq!(app.list_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn test_free_keys_follow_the_selected_binding() {
+        let mut app = test_app("modal");
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('F'));
+        let (title, lines) = app.popup.clone().unwrap();
+        assert_eq!(title, "Free keys: SUPER (91 free)");
+        assert!(lines[0].starts_with("Letters:"));
+        assert!(!lines[0].contains(" K ") && !lines[0].contains(" Q "));
+        assert!(lines[0].contains(" L "));
+    }
+
+    #[test]
+    fn test_diagnostics_count_and_panel() {
+        let mut app = test_app("modal");
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('D'));
+        assert!(app.popup.is_none());
+        assert_eq!(app.status_message.as_deref(), Some("No problems found"));
+
+        let location = crate::diagnostics::Location {
+            file: "binds.conf".into(),
+            line: 3,
+            column: 18,
+        };
+        app.set_diagnostics(vec![
+            Diagnostic::error("missing key", location.clone(), 1, "bind = SUPER, , exec"),
+            Diagnostic::warning("`Super + Q` is already bound", location, 1, "  bind = x  ")
+                .with_note("first bound at binds.conf:1:1 to `killactive`"),
+        ]);
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let hint = app.click_areas.diagnostics;
+        let buffer = terminal.backend().buffer();
+        let text: String = (hint.x..hint.x + hint.width)
+            .map(|x| buffer[(x, hint.y)].symbol())
+            .collect();
+        assert_eq!(text, " ⚠ 2 ");
+
+        press(&mut app, KeyCode::Char('D'));
+        let (title, lines) = app.popup.clone().unwrap();
+        assert_eq!(title, "Diagnostics: 1 error, 1 warning");
+        assert_eq!(
+            lines,
+            [
+                "binds.conf:3:18: error: missing key",
+                "    bind = SUPER, , exec",
+                "",
+                "binds.conf:3:18: warning: `Super + Q` is already bound",
+                "    bind = x",
+                "    note: first bound at binds.conf:1:1 to `killactive`",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mouse_can_be_turned_off() {
+        let mut app = test_app("modal");
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 10,
+            row: 6,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        app.config.ui.mouse = false;
+        assert!(!app.handle_mouse(click));
+        assert!(!app.handle_mouse(click));
+        assert_eq!(app.list_state.selected(), Some(0));
+        app.handle_mouse(MouseEvent {
+            kind: MouseEventKind::ScrollDown,
+            ..click
+        });
+        assert_eq!(app.list_state.selected(), Some(0));
+
+        app.config.ui.mouse = true;
+        assert!(!app.handle_mouse(click));
+        assert_eq!(app.list_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn test_table_layout_fits_columns() {
+        let mut app = test_app("modal");
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('t'));
+        app.set_sort_mode(SortMode::Key);
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(44, 12)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let line = |terminal: &Terminal<ratatui::backend::TestBackend>, y| {
+            let buffer = terminal.backend().buffer();
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        };
+
+        // The description gets what the other columns leave, cut with "…"
+        assert_eq!(
+            line(&terminal, 5),
+            "│  Key ▲       Des… Dispatcher   Category  │"
+        );
+        assert_eq!(
+            line(&terminal, 6),
+            "│> Super + K   Lau… exec         Other     │"
+        );
+
+        // Rows start below the header
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 10,
+            row: 7,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.handle_mouse(click);
+        assert_eq!(app.list_state.selected(), Some(1));
+
+        press(&mut app, KeyCode::Char(']'));
+        press(&mut app, KeyCode::Char('<'));
+        assert_eq!(app.table_widths.get(&TableColumn::Key), Some(&9));
+    }
+
+    /// The frame's text, then the same grid with a letter per distinct
+    /// style and a legend, so a snapshot shows where every color lands.
+    fn snapshot(buffer: &ratatui::buffer::Buffer) -> String {
+        let color = |color| match color {
+            ratatui::style::Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+            other => format!("{:?}", other),
+        };
+        let mut styles: Vec<String> = Vec::new();
+        let (mut text, mut grid) = (String::new(), String::new());
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                let cell = &buffer[(x, y)];
+                let style = format!(
+                    "fg={} bg={} {:?}",
+                    color(cell.fg),
+                    color(cell.bg),
+                    cell.modifier
+                );
+                let index = styles.iter().position(|s| *s == style).unwrap_or_else(|| {
+                    styles.push(style);
+                    styles.len() - 1
+                });
+                text.push_str(cell.symbol());
+                grid.push((b'a' + index as u8) as char);
+            }
+            text.push('\n');
+            grid.push('\n');
+        }
+        let legend: Vec<String> = styles
+            .iter()
+            .enumerate()
+            .map(|(i, style)| format!("{}: {}", (b'a' + i as u8) as char, style))
+            .collect();
+        format!("{}\n{}\n{}\n", text, grid, legend.join("\n"))
+    }
+
+    #[test]
+    fn test_theme_snapshots() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/snapshots");
+        for preset in [
+            "catppuccin_mocha",
+            "catppuccin_latte",
+            "catppuccin_macchiato",
+            "catppuccin_frappe",
+        ] {
+            let mut app = test_app("modal");
+            app.theme = Theme::new(&crate::config::ThemeSettings::from_name(preset).unwrap());
+            app.state.toggle_pin(&app.keybindings[1].id());
+            app.keybindings[0].inactive = true;
+            app.show_inactive = true;
+            app.filter_keybindings();
+
+            let mut actual = String::new();
+            let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(48, 12)).unwrap();
+            terminal.draw(|f| app.ui(f)).unwrap();
+            actual.push_str(&snapshot(terminal.backend().buffer()));
+            app.open_popup("Popup".to_string(), vec!["line".to_string()]);
+            terminal.draw(|f| app.ui(f)).unwrap();
+            actual.push_str(&snapshot(terminal.backend().buffer()));
+            app.show_help = true;
+            terminal.draw(|f| app.ui(f)).unwrap();
+            actual.push_str(&snapshot(terminal.backend().buffer()));
+
+            testutil::assert_golden(&dir.join(format!("theme_{}.txt", preset)), &actual);
+        }
+    }
+
+    #[test]
+    fn test_ctrl_style_never_leaves_insert() {
+        let mut app = test_app("ctrl");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.input_mode, InputMode::Insert);
+        app.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL));
+        assert_eq!(app.list_state.selected(), Some(1));
+        assert!(press(&mut app, KeyCode::Esc));
+    }
+
+    #[test]
+    fn test_every_key_goes_through_the_keymap() {
+        let mut app = test_app("modal");
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        app.handle_paste("kit");
+        app.handle_key(ctrl('a'));
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.search.value(), "xkit");
+        app.handle_key(ctrl('u'));
+        assert!(app.search.value().is_empty());
+
+        // The keyboard view toggles modifiers by name; Super starts on
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('K'));
+        press(&mut app, KeyCode::Char('1'));
+        press(&mut app, KeyCode::Char('4'));
+        assert_eq!(app.keyboard.modifiers, ["ALT"]);
+
+        // Ctrl-c quits whatever the keymap says
+        app.config.ui.keymap = serde_yaml::from_str("quit: Q").unwrap();
+        assert!(!press(&mut app, KeyCode::Char('q')));
+        assert!(app.handle_key(ctrl('c')));
+        app.input_mode = InputMode::Insert;
+        assert!(app.handle_key(ctrl('c')));
+    }
+
+    #[test]
+    fn test_copy_execute_edit_and_detail_actions() {
+        let mut app = test_app("modal");
+        app.config.ui.keymap = serde_yaml::from_str("execute: x").unwrap();
+        press(&mut app, KeyCode::Esc);
+
+        press(&mut app, KeyCode::Char('d'));
+        let (title, lines) = app.popup.take().unwrap();
+        assert_eq!(title, "Keybinding");
+        assert_eq!(lines[0], "Key:         Super + K");
+
+        press(&mut app, KeyCode::Char('e'));
+        assert!(app.edit_request.is_some());
+
+        assert!(press(&mut app, KeyCode::Char('x')));
+        assert!(matches!(
+            &app.exit_action,
+            Some(ExitAction::Execute(kb)) if kb.action == "exec, kitty"
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+/// Watches a set of files for changes.
+///
+/// The parent directories are watched rather than the files themselves,
+/// since most editors save by writing a new file and renaming it over the
+/// old one, which would end an inotify watch on the file.
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    dirs: HashSet<PathBuf>,
+    files: HashSet<(PathBuf, OsString)>,
+}
+
+impl FileWatcher {
+    pub fn new(paths: &[PathBuf]) -> Result<Self> {
+        let (tx, events) = channel();
+        let watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+        let mut watcher = Self {
+            watcher,
+            events,
+            dirs: HashSet::new(),
+            files: HashSet::new(),
+        };
+        watcher.set_paths(paths)?;
+        Ok(watcher)
+    }
+
+    /// Replace the watched files, e.g. after a `source` line was added.
+    pub fn set_paths(&mut self, paths: &[PathBuf]) -> Result<()> {
+        let mut dirs = HashSet::new();
+        self.files.clear();
+        for path in paths {
+            if let Some(key) = watch_key(path) {
+                dirs.insert(key.0.clone());
+                self.files.insert(key);
+            }
+        }
+
+        for dir in self.dirs.difference(&dirs) {
+            // The directory may be gone already
+            let _ = self.watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&self.dirs) {
+            self.watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Failed to watch {:?}", dir))?;
+        }
+        self.dirs = dirs;
+
+        Ok(())
+    }
+
+    /// Whether any watched file changed since the last call. Never blocks.
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        for event in self.events.try_iter().flatten() {
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) && event
+                .paths
+                .iter()
+                .filter_map(|path| watch_key(path))
+                .any(|key| self.files.contains(&key))
+            {
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// The canonical parent directory and file name of `path`, so that paths
+/// reported by the watcher compare equal to the configured ones.
+fn watch_key(path: &Path) -> Option<(PathBuf, OsString)> {
+    let name = path.file_name()?.to_os_string();
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let parent = parent.canonicalize().ok()?;
+    Some((parent, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+    use std::time::Duration;
+
+    #[test]
+    fn test_detects_changes_to_watched_files_only() {
+        let dir = TempDir::new("watch");
+        let watched = dir.write("hyprland.conf", "bind = SUPER, Q, killactive\n");
+
+        let watcher = FileWatcher::new(std::slice::from_ref(&watched)).unwrap();
+        dir.write("other.conf", "");
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!watcher.changed());
+
+        // Save the way editors do: write a new file and rename it over
+        let temp = dir.write("hyprland.conf.tmp", "bind = SUPER, W, killactive\n");
+        std::fs::rename(&temp, &watched).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(watcher.changed());
+    }
+}